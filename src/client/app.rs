@@ -1,21 +1,44 @@
+use std::path::PathBuf;
+
 use winit::{application::ApplicationHandler, event_loop::ControlFlow};
 
-use super::Client;
+use super::{Client, Location};
 
 pub struct ClientApp<'a> {
     client: Option<Client<'a>>,
+    watch: Option<PathBuf>,
+    location: Option<Location>,
+    open: Option<PathBuf>,
+    resume: Option<PathBuf>,
 }
 
 impl ClientApp<'_> {
-    pub fn new() -> Self {
-        Self { client: None }
+    pub fn new(
+        watch: Option<PathBuf>,
+        location: Option<Location>,
+        open: Option<PathBuf>,
+        resume: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            client: None,
+            watch,
+            location,
+            open,
+            resume,
+        }
     }
 }
 
 impl ApplicationHandler for ClientApp<'_> {
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         if self.client.is_none() {
-            self.client = Some(Client::new(event_loop));
+            self.client = Some(Client::new(
+                event_loop,
+                self.watch.clone(),
+                self.location.as_ref(),
+                self.open.as_deref(),
+                self.resume.as_deref(),
+            ));
         }
         event_loop.set_control_flow(ControlFlow::Poll);
     }
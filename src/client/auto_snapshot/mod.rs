@@ -0,0 +1,50 @@
+use super::render::Progress;
+
+#[cfg(test)]
+mod test;
+
+// readings in a row the undecided fraction has to stay within STABLE_CHANGE
+const STABLE_READINGS: u32 = 30;
+const STABLE_CHANGE: f32 = 1e-4;
+// deeper views need more iterations before they're worth keeping
+const BASE_ITERS: u32 = 1000;
+const ITERS_PER_LEVEL: u32 = 100;
+
+// takes a snapshot once the view has mostly finished, so there's something
+// to show when you move again. that's when the undecided pixels stop
+// changing, or after enough iterations for how deep it is; once per view
+#[derive(Default)]
+pub struct AutoSnapshot {
+    last_iters: u32,
+    since: f32,
+    stable: u32,
+    taken: bool,
+}
+
+impl AutoSnapshot {
+    // returns whether to take one now
+    pub fn update(&mut self, progress: Progress, pixels: u32, level: i32) -> bool {
+        let fraction = progress.undecided as f32 / pixels.max(1) as f32;
+        // the shader starts every pixel over when the view changes
+        if progress.iters < self.last_iters {
+            self.taken = false;
+            self.stable = 0;
+            self.since = fraction;
+        }
+        self.last_iters = progress.iters;
+
+        if (fraction - self.since).abs() < STABLE_CHANGE {
+            self.stable += 1;
+        } else {
+            self.since = fraction;
+            self.stable = 0;
+        }
+        // nothing's escaped yet, so it'd be all black
+        if self.taken || progress.undecided >= pixels {
+            return false;
+        }
+        let threshold = BASE_ITERS + ITERS_PER_LEVEL * level.max(0) as u32;
+        self.taken = self.stable >= STABLE_READINGS || progress.iters >= threshold;
+        self.taken
+    }
+}
@@ -0,0 +1,70 @@
+use super::{super::render::Progress, AutoSnapshot, BASE_ITERS, ITERS_PER_LEVEL, STABLE_READINGS};
+
+const PIXELS: u32 = 1000;
+
+fn progress(undecided: u32, iters: u32) -> Progress {
+    Progress { undecided, iters }
+}
+
+#[test]
+fn fires_once_stable() {
+    let mut auto = AutoSnapshot::default();
+    // still changing
+    for i in 0..10 {
+        assert!(!auto.update(progress(900 - i * 50, i + 1), PIXELS, 0));
+    }
+    // the first reading at 400 starts the run
+    let mut fired = None;
+    for i in 0..STABLE_READINGS + 5 {
+        if auto.update(progress(400, 20 + i), PIXELS, 0) {
+            fired = Some(i);
+            break;
+        }
+    }
+    assert_eq!(fired, Some(STABLE_READINGS));
+    // only once per view
+    for i in 0..STABLE_READINGS * 2 {
+        assert!(!auto.update(progress(400, 100 + i), PIXELS, 0));
+    }
+}
+
+#[test]
+fn not_while_undecided() {
+    let mut auto = AutoSnapshot::default();
+    let threshold = BASE_ITERS + ITERS_PER_LEVEL * 5;
+    for i in 0..STABLE_READINGS * 2 {
+        assert!(!auto.update(progress(PIXELS, threshold + i), PIXELS, 5));
+    }
+    // as soon as something escapes
+    assert!(auto.update(progress(PIXELS - 1, threshold * 2), PIXELS, 5));
+}
+
+#[test]
+fn fires_after_enough_iters() {
+    let mut auto = AutoSnapshot::default();
+    let threshold = BASE_ITERS + ITERS_PER_LEVEL * 3;
+    assert!(!auto.update(progress(800, threshold - 1), PIXELS, 3));
+    assert!(!auto.update(progress(700, threshold - 1), PIXELS, 3));
+    assert!(auto.update(progress(600, threshold), PIXELS, 3));
+}
+
+#[test]
+fn resets_on_new_view() {
+    let mut auto = AutoSnapshot::default();
+    for i in 0..=STABLE_READINGS {
+        auto.update(progress(400, 10 + i), PIXELS, 0);
+    }
+    assert!(auto.taken);
+    // the iterations drop when the shader starts over, and it's stable at
+    // the new fraction from there, counting the reading that started over
+    assert!(!auto.update(progress(700, 1), PIXELS, 0));
+    assert!(!auto.taken);
+    let mut fired = None;
+    for i in 0..STABLE_READINGS + 5 {
+        if auto.update(progress(700, 2 + i), PIXELS, 0) {
+            fired = Some(i);
+            break;
+        }
+    }
+    assert_eq!(fired, Some(STABLE_READINGS - 2));
+}
@@ -0,0 +1,154 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+use crate::util::FixedDec;
+
+use super::{
+    camera::{Camera, Zoom},
+    render::{Coloring, Formula},
+};
+
+#[cfg(test)]
+mod test;
+
+// a saved location; positions are written as exact decimals so deep zooms
+// come back to the same pixel
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Bookmark {
+    #[serde(with = "decimal")]
+    pub x: FixedDec,
+    #[serde(with = "decimal")]
+    pub y: FixedDec,
+    pub level: i32,
+    pub exp: f32,
+    pub formula: String,
+    // the coloring it was saved with, which older bookmarks and snapshots
+    // don't have
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub density: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub julia: Option<Point>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Point {
+    #[serde(with = "decimal")]
+    pub x: FixedDec,
+    #[serde(with = "decimal")]
+    pub y: FixedDec,
+}
+
+impl Bookmark {
+    pub fn new(camera: &Camera, formula: &Formula, julia: Option<&Vector2<FixedDec>>) -> Self {
+        Self {
+            x: camera.pos.x.clone(),
+            y: camera.pos.y.clone(),
+            level: camera.zoom.level(),
+            exp: camera.zoom.exp(),
+            formula: formula.to_string(),
+            palette: None,
+            offset: None,
+            density: None,
+            julia: julia.map(|c| Point {
+                x: c.x.clone(),
+                y: c.y.clone(),
+            }),
+        }
+    }
+
+    pub fn with_coloring(mut self, coloring: &Coloring) -> Self {
+        self.palette = Some(coloring.palette.name.clone());
+        self.offset = Some(coloring.offset);
+        self.density = Some(coloring.density);
+        self
+    }
+
+    pub fn camera(&self, size: Vector2<u32>) -> Camera {
+        Camera {
+            pos: Vector2::new(self.x.clone(), self.y.clone()),
+            zoom: Zoom::new(self.level, self.exp),
+            size,
+        }
+    }
+
+    pub fn julia(&self) -> Option<Vector2<FixedDec>> {
+        let c = self.julia.as_ref()?;
+        Some(Vector2::new(c.x.clone(), c.y.clone()))
+    }
+}
+
+// bookmarks on the number keys, saved to bookmarks.toml in the config dir
+// every time one changes
+pub struct Bookmarks {
+    path: Option<PathBuf>,
+    slots: BTreeMap<String, Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn load() -> Self {
+        let path = dirs::config_dir().map(|d| d.join("fractal").join("bookmarks.toml"));
+        let mut res = Self {
+            path,
+            slots: BTreeMap::new(),
+        };
+        let Some(path) = &res.path else {
+            println!("no config dir, bookmarks won't be saved");
+            return res;
+        };
+        let Ok(source) = std::fs::read_to_string(path) else {
+            return res;
+        };
+        match toml::from_str(&source) {
+            Ok(slots) => res.slots = slots,
+            Err(e) => {
+                // don't overwrite a file someone broke by hand
+                println!("{}: {}", path.display(), e);
+                println!("bookmarks won't be saved until it's fixed");
+                res.path = None;
+            }
+        }
+        res
+    }
+
+    pub fn get(&self, slot: u32) -> Option<&Bookmark> {
+        self.slots.get(&slot.to_string())
+    }
+
+    pub fn set(&mut self, slot: u32, bookmark: Bookmark) {
+        self.slots.insert(slot.to_string(), bookmark);
+        if let Err(e) = self.save() {
+            println!("could not save bookmarks: {}", e);
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let source = toml::to_string(&self.slots).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+mod decimal {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::util::FixedDec;
+
+    pub fn serialize<S: Serializer>(x: &FixedDec, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(x)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<FixedDec, D::Error> {
+        String::deserialize(d)?.parse().map_err(D::Error::custom)
+    }
+}
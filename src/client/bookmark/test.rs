@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use nalgebra::Vector2;
+
+use crate::util::FixedDec;
+
+use super::{
+    super::{
+        camera::{Camera, Zoom},
+        render::{Coloring, Formula},
+    },
+    Bookmark,
+};
+
+const DEEP: &str = "-1.749957683706093503602214506070699707271105797262520779302428378202860080829728048872186727844317008311005445076556595313797475419999999951";
+
+fn camera() -> Camera {
+    Camera {
+        pos: Vector2::new(
+            DEEP.parse().unwrap(),
+            "0.000000000000000000000000000000000000000000000000000000000000000000012345"
+                .parse()
+                .unwrap(),
+        ),
+        zoom: Zoom::new(440, -0.375),
+        size: Vector2::new(640, 360),
+    }
+}
+
+// through toml the way Bookmarks saves them, keyed by slot
+fn round_trip(bookmark: &Bookmark) -> Bookmark {
+    let slots = BTreeMap::from([("1".to_string(), bookmark.clone())]);
+    let source = toml::to_string(&slots).unwrap();
+    let mut back: BTreeMap<String, Bookmark> = toml::from_str(&source).unwrap();
+    back.remove("1").unwrap()
+}
+
+#[test]
+fn toml_round_trip() {
+    let c = Vector2::new(
+        FixedDec::from(-0.75),
+        "0.10156250000000000000000000000000000001".parse().unwrap(),
+    );
+    let coloring = Coloring {
+        offset: 0.25,
+        density: 3.5,
+        ..Default::default()
+    };
+    let bookmark =
+        Bookmark::new(&camera(), &Formula::Multibrot(3), Some(&c)).with_coloring(&coloring);
+    let back = round_trip(&bookmark);
+    let size = camera().size;
+    assert_eq!(back.camera(size).pos, camera().pos);
+    assert_eq!(back.level, 440);
+    assert_eq!(back.exp, -0.375);
+    assert_eq!(back.formula, "multibrot 3");
+    assert_eq!(back.palette, Some(coloring.palette.name.clone()));
+    assert_eq!(back.offset, Some(0.25));
+    assert_eq!(back.density, Some(3.5));
+    assert_eq!(back.julia(), Some(c));
+}
+
+#[test]
+fn optional_fields() {
+    let bookmark = Bookmark::new(&camera(), &Formula::Mandelbrot, None);
+    let source = toml::to_string(&bookmark).unwrap();
+    for field in ["palette", "offset", "density", "julia"] {
+        assert!(!source.contains(field), "{}", source);
+    }
+    let back = round_trip(&bookmark);
+    assert_eq!(back.julia(), None);
+    assert_eq!(
+        (back.palette, back.offset, back.density),
+        (None, None, None)
+    );
+    // an exact decimal, not a float that would round it
+    let bad = source.replace(&format!("\"{}\"", bookmark.x), "-1.75");
+    assert_ne!(bad, source);
+    assert!(toml::from_str::<Bookmark>(&bad).is_err());
+}
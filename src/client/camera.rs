@@ -3,6 +3,8 @@ use std::ops::AddAssign;
 
 use crate::util::FixedDec;
 
+const INV_MULT_PRECISION: usize = 3;
+
 #[derive(Clone)]
 pub struct Zoom {
     exp: f32,
@@ -70,6 +72,17 @@ impl Default for Camera {
     }
 }
 
+impl Camera {
+    // julia sets are centered on 0 instead of the mandelbrot's -0.5
+    pub fn julia(size: Vector2<u32>) -> Self {
+        Self {
+            size,
+            pos: Vector2::new(0.0, 0.0).map(FixedDec::from),
+            zoom: Zoom::new(0, 2.1),
+        }
+    }
+}
+
 impl Zoom {
     pub fn new(level: i32, scale: f32) -> Self {
         Self {
@@ -88,6 +101,13 @@ impl Zoom {
     pub fn level(&self) -> i32 {
         self.level
     }
+    pub fn exp(&self) -> f32 {
+        self.exp
+    }
+    // mult is 2 to this
+    pub fn log2_mult(&self) -> f64 {
+        self.exp as f64 - self.level as f64
+    }
 }
 
 impl AddAssign<f32> for Zoom {
@@ -112,5 +132,5 @@ pub fn zoom_mult(level: i32, exp: f32) -> FixedDec {
 }
 
 pub fn inv_zoom_mult(level: i32, exp: f32) -> FixedDec {
-    (FixedDec::from(1) << level) * FixedDec::from(1.0 / exp.exp2())
+    zoom_mult(level, exp).recip(INV_MULT_PRECISION)
 }
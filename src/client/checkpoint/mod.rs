@@ -0,0 +1,177 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use nalgebra::Vector2;
+
+use crate::util::FixedDec;
+
+use super::{
+    render::{ComputeMode, Formula, WorkState},
+    Location,
+};
+
+#[cfg(test)]
+mod test;
+
+const MAGIC: &[u8; 8] = b"FRACCKPT";
+// 2 added dz/dc to the work buffer, 3 the formula's hash
+const VERSION: u32 = 3;
+
+// everything the compute shader has done so far, so it can carry on in
+// another session. the work buffer is only valid for the exact view bytes
+// it was iterated with, so those are kept too and checked on restore
+pub struct Checkpoint {
+    pub location: Location,
+    // formula_hash of what the work was iterated with, since a script can
+    // change under the same name
+    pub formula: u64,
+    pub julia: Option<Vector2<FixedDec>>,
+    pub size: Vector2<u32>,
+    pub state: WorkState,
+}
+
+// where ctrl+k saves and --resume reads by default
+pub fn default_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("fractal").join("checkpoint.bin"))
+}
+
+// fnv-1a over the generated wgsl, which unlike the std hasher stays the same
+// between builds
+pub fn formula_hash(formula: &Formula) -> u64 {
+    let source = [formula.to_string(), formula.bailout(), formula.body()].join("\0");
+    source.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Checkpoint {
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        let mut w = BufWriter::new(file);
+        let julia = self
+            .julia
+            .as_ref()
+            .map(|c| format!("{}:{}", c.x.to_hex(), c.y.to_hex()))
+            .unwrap_or_default();
+        let mode = match self.state.mode {
+            ComputeMode::Direct => 0,
+            ComputeMode::Perturbation => 1,
+        };
+        let work: Vec<u8> = self
+            .state
+            .work
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+        let mut write = || -> std::io::Result<()> {
+            w.write_all(MAGIC)?;
+            write_u32(&mut w, VERSION)?;
+            write_bytes(&mut w, self.location.to_string().as_bytes())?;
+            w.write_all(&self.formula.to_le_bytes())?;
+            write_bytes(&mut w, julia.as_bytes())?;
+            write_u32(&mut w, mode)?;
+            write_u32(&mut w, self.state.len as u32)?;
+            write_u32(&mut w, self.size.x)?;
+            write_u32(&mut w, self.size.y)?;
+            write_bytes(&mut w, &self.state.view)?;
+            write_bytes(&mut w, &work)?;
+            w.flush()
+        };
+        write().map_err(|e| e.to_string())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut r = BufReader::new(file);
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic).map_err(|e| e.to_string())?;
+        if &magic != MAGIC {
+            return Err("not a checkpoint".to_string());
+        }
+        let err = |e: std::io::Error| format!("truncated checkpoint: {}", e);
+        let version = read_u32(&mut r).map_err(err)?;
+        if version != VERSION {
+            return Err(format!(
+                "checkpoint is version {}, this only reads {}",
+                version, VERSION
+            ));
+        }
+        let string = |r: &mut BufReader<File>| -> Result<String, String> {
+            String::from_utf8(read_bytes(r).map_err(err)?).map_err(|e| e.to_string())
+        };
+        let location = string(&mut r)?.parse()?;
+        let mut formula = [0; 8];
+        r.read_exact(&mut formula).map_err(err)?;
+        let julia = match string(&mut r)?.as_str() {
+            "" => None,
+            c => {
+                let (x, y) = c.split_once(':').ok_or("invalid julia point")?;
+                let hex = |s| FixedDec::from_hex(s).map_err(|e| format!("julia '{}': {}", s, e));
+                Some(Vector2::new(hex(x)?, hex(y)?))
+            }
+        };
+        let mode = match read_u32(&mut r).map_err(err)? {
+            0 => ComputeMode::Direct,
+            1 => ComputeMode::Perturbation,
+            m => return Err(format!("unknown mode {}", m)),
+        };
+        let len = read_u32(&mut r).map_err(err)? as usize;
+        let size = Vector2::new(
+            read_u32(&mut r).map_err(err)?,
+            read_u32(&mut r).map_err(err)?,
+        );
+        let view = read_bytes(&mut r).map_err(err)?;
+        let work = read_bytes(&mut r).map_err(err)?;
+        if work.len() % 4 != 0 {
+            return Err("work isn't whole u32s".to_string());
+        }
+        Ok(Self {
+            location,
+            formula: u64::from_le_bytes(formula),
+            julia,
+            size,
+            state: WorkState {
+                mode,
+                len,
+                view,
+                work: work
+                    .chunks_exact(4)
+                    .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                    .collect(),
+            },
+        })
+    }
+}
+
+fn write_u32(w: &mut impl Write, v: u32) -> std::io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn write_bytes(w: &mut impl Write, bytes: &[u8]) -> std::io::Result<()> {
+    w.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    w.write_all(bytes)
+}
+
+fn read_u32(r: &mut impl Read) -> std::io::Result<u32> {
+    let mut b = [0; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+fn read_bytes(r: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut b = [0; 8];
+    r.read_exact(&mut b)?;
+    let len = u64::from_le_bytes(b);
+    let mut res = Vec::new();
+    r.take(len).read_to_end(&mut res)?;
+    if res.len() as u64 != len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(res)
+}
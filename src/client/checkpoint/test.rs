@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+
+use nalgebra::Vector2;
+
+use crate::util::{FixedDec, Script};
+
+use super::{
+    super::{
+        render::{ComputeMode, Formula, WorkState},
+        Location,
+    },
+    formula_hash, Checkpoint, MAGIC, VERSION,
+};
+
+// a file of its own in the temp dir for each test, since they run at once
+fn temp(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fractal-{}-{}.bin", name, std::process::id()))
+}
+
+fn checkpoint() -> Checkpoint {
+    Checkpoint {
+        location: Location {
+            pos: Vector2::new(FixedDec::from(-0.75), FixedDec::from(0.125)),
+            level: 40,
+            exp: 0.25,
+            formula: Some("burning_ship".to_string()),
+            julia: None,
+        },
+        formula: formula_hash(&Formula::BurningShip),
+        julia: Some(Vector2::new(FixedDec::from(-0.5), FixedDec::from(0.5))),
+        size: Vector2::new(3, 2),
+        state: WorkState {
+            mode: ComputeMode::Perturbation,
+            len: 4,
+            view: vec![1, 2, 3, 255],
+            work: vec![0, 1, u32::MAX, 0xdeadbeef],
+        },
+    }
+}
+
+#[test]
+fn round_trip() {
+    let path = temp("round-trip");
+    let saved = checkpoint();
+    saved.write(&path).unwrap();
+    let back = Checkpoint::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(back.location.to_string(), saved.location.to_string());
+    assert_eq!(back.formula, saved.formula);
+    assert_eq!(back.julia, saved.julia);
+    assert_eq!(back.size, saved.size);
+    assert_eq!(back.state.mode, saved.state.mode);
+    assert_eq!(back.state.len, saved.state.len);
+    assert_eq!(back.state.view, saved.state.view);
+    assert_eq!(back.state.work, saved.state.work);
+
+    let path = temp("no-julia");
+    let saved = Checkpoint {
+        julia: None,
+        ..checkpoint()
+    };
+    saved.write(&path).unwrap();
+    let back = Checkpoint::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(back.julia, None);
+}
+
+fn read_bytes(name: &str, bytes: &[u8]) -> Result<Checkpoint, String> {
+    let path = temp(name);
+    std::fs::write(&path, bytes).unwrap();
+    let res = Checkpoint::read(&path);
+    std::fs::remove_file(&path).unwrap();
+    res
+}
+
+fn written(name: &str) -> Vec<u8> {
+    let path = temp(name);
+    checkpoint().write(&path).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    bytes
+}
+
+#[test]
+fn bad_magic() {
+    let mut bytes = written("bad-magic");
+    bytes[0] = b'X';
+    let err = read_bytes("bad-magic", &bytes).err().unwrap();
+    assert_eq!(err, "not a checkpoint");
+    assert!(read_bytes("empty", &[]).is_err());
+}
+
+#[test]
+fn wrong_version() {
+    let mut bytes = written("wrong-version");
+    assert_eq!(&bytes[..8], MAGIC);
+    bytes[8..12].copy_from_slice(&(VERSION - 1).to_le_bytes());
+    let err = read_bytes("wrong-version", &bytes).err().unwrap();
+    assert!(err.starts_with("checkpoint is version"), "{}", err);
+}
+
+#[test]
+fn truncated() {
+    let bytes = written("truncated");
+    // anywhere past the magic, including partway through the work
+    for len in [10, 12, 20, bytes.len() - 5, bytes.len() - 1] {
+        let err = read_bytes("truncated", &bytes[..len]).err().unwrap();
+        assert!(err.starts_with("truncated checkpoint"), "{}: {}", len, err);
+    }
+}
+
+#[test]
+fn formula_hashes() {
+    let hash = formula_hash(&Formula::Mandelbrot);
+    assert_eq!(hash, formula_hash(&Formula::Mandelbrot));
+    assert_ne!(hash, formula_hash(&Formula::Tricorn));
+    assert_ne!(
+        formula_hash(&Formula::Multibrot(3)),
+        formula_hash(&Formula::Multibrot(4))
+    );
+    // a script edited under the same name
+    let script = |body: &str| Formula::Script {
+        name: "test".to_string(),
+        script: Script {
+            bailout: "let escaped = gt(add(x2, y2), thresh);".to_string(),
+            body: body.to_string(),
+        },
+    };
+    assert_ne!(
+        formula_hash(&script("y = add(mul(x, y), cy); x = add(sub(x2, y2), cx);")),
+        formula_hash(&script("y = add(mul(x, y), cy); x = sub(sub(x2, y2), cx);"))
+    );
+}
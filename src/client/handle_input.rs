@@ -1,27 +1,116 @@
-use std::time::Duration;
+use std::{path::Path, time::Duration};
 
 use winit::{event::MouseButton, keyboard::KeyCode as K};
 
 use crate::util::FixedDec;
 
-use super::Client;
+use super::{
+    bookmark::Bookmark,
+    camera::Camera,
+    julia::Julia,
+    location::Location,
+    render::{Coloring, ComputeMode, Formula, Palette, SnapshotKind},
+    Client,
+};
+
+// scripts in here get added after the builtin formulas
+pub const FORMULA_DIR: &str = "formulas";
+// and .map and .ugr gradients after the builtin palettes
+pub const PALETTE_DIR: &str = "palettes";
+
+// bookmark slots 1 to 9
+const DIGITS: [K; 9] = [
+    K::Digit1,
+    K::Digit2,
+    K::Digit3,
+    K::Digit4,
+    K::Digit5,
+    K::Digit6,
+    K::Digit7,
+    K::Digit8,
+    K::Digit9,
+];
 
 pub struct InputHandling {
-    pub snapshot: bool,
+    pub snapshot: Option<SnapshotKind>,
+    pub mode: ComputeMode,
+    pub formulas: Vec<Formula>,
+    pub formula: usize,
+    pub palettes: Vec<Palette>,
+    pub palette: usize,
+    pub coloring: Coloring,
 }
 
 impl InputHandling {
     pub fn new() -> Self {
-        Self { snapshot: false }
+        Self {
+            snapshot: None,
+            mode: ComputeMode::default(),
+            formulas: Self::load_formulas(Path::new(FORMULA_DIR)),
+            formula: 0,
+            palettes: Palette::builtin()
+                .into_iter()
+                .chain(Palette::load_dir(Path::new(PALETTE_DIR)))
+                .collect(),
+            palette: 0,
+            coloring: Coloring::default(),
+        }
+    }
+
+    fn load_formulas(dir: &Path) -> Vec<Formula> {
+        Formula::builtin()
+            .into_iter()
+            .chain(Formula::load_dir(dir))
+            .collect()
+    }
+
+    // replaces the scripts with the ones in dir, staying on the current
+    // formula if it's still there; a script that's still there but doesn't
+    // parse anymore keeps its last version instead of disappearing
+    pub fn reload_formulas(&mut self, dir: &Path) {
+        let current = self.formula().to_string();
+        let mut formulas = Self::load_formulas(dir);
+        let broken: Vec<Formula> = std::mem::take(&mut self.formulas)
+            .into_iter()
+            .filter(|f| match f {
+                Formula::Script { name, .. } => {
+                    dir.join(format!("{name}.formula")).is_file()
+                        && formulas.iter().all(|n| n.to_string() != *name)
+                }
+                _ => false,
+            })
+            .collect();
+        formulas.extend(broken);
+        formulas[Formula::builtin().len()..].sort_by_key(|f| f.to_string());
+        self.formulas = formulas;
+        self.formula = self
+            .formulas
+            .iter()
+            .position(|f| f.to_string() == current)
+            .unwrap_or(0);
+    }
+
+    pub fn formula(&self) -> &Formula {
+        &self.formulas[self.formula]
     }
 }
 
 impl Client<'_> {
     pub fn handle_input(&mut self, delta: Duration) {
+        let ctrl = self.input.pressed(K::ControlLeft) || self.input.pressed(K::ControlRight);
+        if ctrl && self.input.just_pressed(K::KeyE) {
+            self.export();
+        }
+        if ctrl && self.input.just_pressed(K::KeyK) {
+            self.save_checkpoint();
+        }
         let Client {
             input,
             camera,
             handling,
+            julia,
+            bookmarks,
+            clipboard,
             ..
         } = self;
         if delta > Duration::from_secs_f32(0.5) {
@@ -57,7 +146,160 @@ impl Client<'_> {
             camera.pos.x += &speed;
         }
         if input.just_pressed(K::KeyQ) || input.mouse_just_pressed(MouseButton::Right) {
-            handling.snapshot = true;
+            handling.snapshot = Some(SnapshotKind::Manual);
+        }
+        if input.just_pressed(K::KeyP) {
+            handling.mode = handling.mode.toggle();
+            println!("mode: {:?}", handling.mode);
+        }
+        if input.just_pressed(K::KeyB) {
+            handling.coloring.smooth = !handling.coloring.smooth;
+            println!("smooth coloring: {}", handling.coloring.smooth);
+        }
+        if input.just_pressed(K::KeyH) {
+            handling.coloring.equalize = !handling.coloring.equalize;
+            println!("equalized coloring: {}", handling.coloring.equalize);
+        }
+        if input.just_pressed(K::KeyL) {
+            handling.coloring.shading = handling.coloring.shading.next();
+            println!("shading: {}", handling.coloring.shading);
         }
+        if input.just_pressed(K::KeyG) {
+            handling.palette = (handling.palette + 1) % handling.palettes.len();
+            handling.coloring.palette = handling.palettes[handling.palette].clone();
+            println!("palette: {}", handling.coloring.palette);
+        }
+        // held down to slide the palette along or stretch it
+        let coloring = &mut handling.coloring;
+        if input.pressed(K::Period) {
+            coloring.offset = (coloring.offset + per_sec * 0.25).fract();
+        }
+        if input.pressed(K::Comma) {
+            coloring.offset = (coloring.offset - per_sec * 0.25).rem_euclid(1.0);
+        }
+        if input.pressed(K::BracketRight) {
+            coloring.density *= per_sec.exp2();
+        }
+        if input.pressed(K::BracketLeft) {
+            coloring.density /= per_sec.exp2();
+        }
+        if input.just_pressed(K::KeyF) {
+            handling.formula = (handling.formula + 1) % handling.formulas.len();
+            let formula = handling.formula();
+            println!("formula: {}", formula);
+            if !formula.perturbable() && handling.mode == ComputeMode::Perturbation {
+                println!("no perturbation for this formula, using direct mode");
+            }
+        }
+        if input.just_pressed(K::KeyJ) || input.mouse_just_pressed(MouseButton::Middle) {
+            if julia.as_ref().is_some_and(|j| j.active) {
+                println!("already viewing a julia set, tab to go back first");
+            } else {
+                let c = camera.world_pos(input.mouse_pos);
+                println!("julia: {} + {}i", f64::from(&c.x), f64::from(&c.y));
+                let parent = std::mem::replace(camera, Camera::julia(camera.size));
+                *julia = Some(Julia::new(c, parent));
+            }
+        }
+        if input.just_pressed(K::Tab) {
+            if let Some(julia) = julia {
+                julia.toggle(camera);
+            }
+        }
+        if input.just_pressed(K::KeyR) {
+            *camera = Camera {
+                size: camera.size,
+                ..Default::default()
+            };
+            *julia = None;
+        }
+        let shift = input.pressed(K::ShiftLeft) || input.pressed(K::ShiftRight);
+        for (slot, &key) in (1..).zip(&DIGITS) {
+            if !input.just_pressed(key) {
+                continue;
+            }
+            if shift {
+                let c = julia.as_ref().and_then(Julia::c);
+                let bookmark =
+                    Bookmark::new(camera, handling.formula(), c).with_coloring(&handling.coloring);
+                bookmarks.set(slot, bookmark);
+                println!("saved bookmark {}", slot);
+                continue;
+            }
+            let Some(bookmark) = bookmarks.get(slot) else {
+                println!("no bookmark {}, shift+{} saves one", slot, slot);
+                continue;
+            };
+            match handling
+                .formulas
+                .iter()
+                .position(|f| f.to_string() == bookmark.formula)
+            {
+                Some(i) => handling.formula = i,
+                None => println!(
+                    "no formula '{}', staying on {}",
+                    bookmark.formula,
+                    handling.formula()
+                ),
+            }
+            if let Some(name) = &bookmark.palette {
+                match handling.palettes.iter().position(|p| p.name == *name) {
+                    Some(i) => {
+                        handling.palette = i;
+                        handling.coloring.palette = handling.palettes[i].clone();
+                    }
+                    None => println!(
+                        "no palette '{}', staying on {}",
+                        name, handling.coloring.palette
+                    ),
+                }
+            }
+            let coloring = &mut handling.coloring;
+            coloring.offset = bookmark.offset.unwrap_or(coloring.offset);
+            coloring.density = bookmark.density.unwrap_or(coloring.density);
+            // the mandelbrot view behind a julia bookmark starts over
+            *camera = bookmark.camera(camera.size);
+            *julia = bookmark.julia().map(|c| Julia::new(c, Camera::default()));
+            println!("loaded bookmark {}", slot);
+        }
+        if ctrl && input.just_pressed(K::KeyC) {
+            let c = julia.as_ref().and_then(Julia::c);
+            let location = Location::new(camera, handling.formula(), c).to_string();
+            println!("{}", location);
+            if let Some(clipboard) = clipboard {
+                match clipboard.set_text(&location) {
+                    Ok(()) => println!("copied location"),
+                    Err(e) => println!("could not copy location: {}", e),
+                }
+            }
+        }
+        if ctrl && input.just_pressed(K::KeyV) {
+            let text = clipboard.as_mut().map(|c| c.get_text());
+            match text.map(|t| t.map_err(|e| e.to_string())?.parse::<Location>()) {
+                Some(Ok(location)) => go_to(&location, camera, handling, julia),
+                Some(Err(e)) => println!("could not paste location: {}", e),
+                None => (),
+            }
+        }
+    }
+}
+
+pub fn go_to(
+    location: &Location,
+    camera: &mut Camera,
+    handling: &mut InputHandling,
+    julia: &mut Option<Julia>,
+) {
+    *camera = location.camera(camera.size);
+    // like a bookmark, the mandelbrot view behind a julia set starts over
+    *julia = location
+        .julia
+        .clone()
+        .map(|c| Julia::new(c, Camera::default()));
+    if let Some(i) = location.find_formula(&handling.formulas) {
+        handling.formula = i;
+    } else if let Some(name) = &location.formula {
+        println!("no formula '{}', staying on {}", name, handling.formula());
     }
+    println!("went to {}", location);
 }
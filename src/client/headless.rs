@@ -0,0 +1,171 @@
+use std::{fs::File, io::BufWriter, path::PathBuf, time::Instant};
+
+use nalgebra::Vector2;
+
+use crate::util::FixedDec;
+
+use super::{
+    camera::Camera,
+    handle_input::{FORMULA_DIR, PALETTE_DIR},
+    interop,
+    render::{render_cpu, render_headless, Coloring, Formula, Palette, Shading},
+};
+
+#[derive(clap::Args)]
+pub struct RenderArgs {
+    #[arg(long, default_value = "-0.5", allow_hyphen_values = true)]
+    center_x: FixedDec,
+    #[arg(long, default_value = "0", allow_hyphen_values = true)]
+    center_y: FixedDec,
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        help = "How many times the view has been zoomed in by 2 from the starting one"
+    )]
+    zoom: f32,
+    #[arg(long, default_value = "1920x1080", value_parser = parse_size, value_name = "WxH")]
+    size: Vector2<u32>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["center_x", "center_y", "zoom"],
+        help = "Render the view in a Kalles Fraktaler .kfr or XaoS .xpf file"
+    )]
+    open: Option<PathBuf>,
+    #[arg(long, help = "[default: 1000, or the file's]")]
+    iters: Option<u32>,
+    #[arg(long, help = "[default: mandelbrot, or the file's]")]
+    formula: Option<String>,
+    #[arg(long, help = "Color by whole iterations instead of smoothly")]
+    banded: bool,
+    #[arg(
+        long,
+        help = "Spread the palette over how many pixels escaped at each iteration"
+    )]
+    equalize: bool,
+    #[arg(long, default_value = "hue")]
+    palette: String,
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        allow_hyphen_values = true,
+        help = "How far along to start the palette, in loops"
+    )]
+    offset: f32,
+    #[arg(
+        long,
+        default_value_t = 1.0,
+        help = "How many times faster the palette loops"
+    )]
+    density: f32,
+    #[arg(
+        long,
+        default_value = "none",
+        help = "Shade by the distance estimate: none, lines, distance, lambert, or normal"
+    )]
+    shading: String,
+    #[arg(long, help = "Use a software adapter even if there's a gpu")]
+    software: bool,
+    #[arg(
+        long,
+        conflicts_with = "software",
+        help = "Render on the cpu without wgpu, much slower but gives the same image"
+    )]
+    cpu: bool,
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+fn parse_size(s: &str) -> Result<Vector2<u32>, String> {
+    let err = || format!("expected WIDTHxHEIGHT, found '{}'", s);
+    let (w, h) = s.split_once('x').ok_or_else(err)?;
+    let size = Vector2::new(w.parse().map_err(|_| err())?, h.parse().map_err(|_| err())?);
+    if size.x == 0 || size.y == 0 {
+        return Err(err());
+    }
+    Ok(size)
+}
+
+pub fn render(args: RenderArgs) -> Result<(), String> {
+    let formulas: Vec<_> = Formula::builtin()
+        .into_iter()
+        .chain(Formula::load_dir(FORMULA_DIR.as_ref()))
+        .collect();
+    let imported = match &args.open {
+        Some(path) => Some(interop::import(path, args.size)?),
+        None => None,
+    };
+    let name = match (
+        &args.formula,
+        imported.as_ref().and_then(|i| i.formula.as_ref()),
+    ) {
+        (Some(name), _) => name.clone(),
+        (None, Some(formula)) => formula.to_string(),
+        (None, None) => "mandelbrot".to_string(),
+    };
+    let formula = formulas
+        .iter()
+        .find(|f| f.to_string() == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = formulas.iter().map(|f| f.to_string()).collect();
+            format!("no formula '{}', there's {}", name, names.join(", "))
+        })?;
+    let iters = args
+        .iters
+        .or(imported.as_ref().and_then(|i| i.iters))
+        .unwrap_or(1000);
+
+    let camera = match imported {
+        Some(imported) => imported.camera,
+        None => {
+            let mut camera = Camera {
+                pos: Vector2::new(args.center_x, args.center_y),
+                size: args.size,
+                ..Default::default()
+            };
+            camera.zoom += args.zoom;
+            camera
+        }
+    };
+
+    let palette = Palette::builtin()
+        .into_iter()
+        .chain(Palette::load_dir(PALETTE_DIR.as_ref()))
+        .find(|p| p.name == args.palette)
+        .ok_or_else(|| format!("no palette '{}'", args.palette))?;
+    let shading = Shading::ALL
+        .into_iter()
+        .find(|s| s.to_string() == args.shading)
+        .ok_or_else(|| format!("no shading '{}'", args.shading))?;
+    let coloring = Coloring {
+        smooth: !args.banded,
+        equalize: args.equalize,
+        palette,
+        offset: args.offset,
+        density: args.density,
+        shading,
+    };
+
+    let start = Instant::now();
+    let data = if args.cpu {
+        render_cpu(&camera, formula, iters, &coloring)?
+    } else {
+        render_headless(&camera, formula, iters, &coloring, args.software)?
+    };
+
+    let file = File::create(&args.output)
+        .map_err(|e| format!("could not create {}: {}", args.output.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), args.size.x, args.size.y);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut w| w.write_image_data(&data))
+        .map_err(|e| format!("could not write {}: {}", args.output.display(), e))?;
+    println!(
+        "wrote {} in {:.2}s",
+        args.output.display(),
+        start.elapsed().as_secs_f32()
+    );
+    Ok(())
+}
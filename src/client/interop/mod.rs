@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod test;
+
+use std::{collections::HashMap, f64::consts::LOG10_2, path::Path};
+
+use nalgebra::Vector2;
+
+use crate::util::FixedDec;
+
+use super::{
+    camera::{Camera, Zoom},
+    render::Formula,
+};
+
+// views saved by other deep zoom programs. kalles fraktaler's .kfr has the
+// center in decimal and a zoom where the image is 4 / zoom tall, and xaos's
+// .xpf has the center and the width and height of what's visible. ours is
+// a power of 2 that the longer side of the screen spans, so going between
+// them depends on the size of the image
+pub struct Imported {
+    pub camera: Camera,
+    pub formula: Option<Formula>,
+    pub iters: Option<u32>,
+}
+
+pub fn import(path: &Path, size: Vector2<u32>) -> Result<Imported, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let camera = Camera {
+        size,
+        ..Default::default()
+    };
+    let res = match path.extension().and_then(|e| e.to_str()) {
+        Some("kfr") => parse_kfr(&source, camera),
+        Some("xpf") => parse_xpf(&source, camera),
+        _ => Err("only .kfr and .xpf files can be opened".to_string()),
+    };
+    res.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// kalles fraktaler's fractal types, with the power for the mandelbrot
+const KFR_TYPES: [(u32, Formula); 5] = [
+    (0, Formula::Mandelbrot),
+    (1, Formula::BurningShip),
+    (2, Formula::Buffalo),
+    (3, Formula::Celtic),
+    (4, Formula::Tricorn),
+];
+
+fn parse_kfr(source: &str, mut camera: Camera) -> Result<Imported, String> {
+    let fields: HashMap<_, _> = source
+        .lines()
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .collect();
+    let num = |key| {
+        let s: &str = fields.get(key).ok_or_else(|| format!("no {}", key))?;
+        s.parse::<FixedDec>()
+            .map_err(|e| format!("{} '{}': {}", key, s, e))
+    };
+    let int = |key| fields.get(key).and_then(|s| s.parse::<u32>().ok());
+
+    camera.pos = Vector2::new(num("Re")?, num("Im")?);
+    let zoom = num("Zoom")?;
+    if zoom.is_neg() || zoom.log2().is_infinite() {
+        return Err(format!("Zoom has to be positive, found {}", zoom));
+    }
+    let height = 2.0 - zoom.log2();
+    camera.zoom = zoom_from_log2(height - (camera.stretch().y as f64).log2());
+
+    let power = int("Power").unwrap_or(2);
+    let formula = match int("FractalType").unwrap_or(0) {
+        0 if power > 2 => Some(Formula::Multibrot(power)),
+        t if power == 2 => KFR_TYPES.iter().find(|k| k.0 == t).map(|k| k.1.clone()),
+        _ => None,
+    };
+    Ok(Imported {
+        camera,
+        formula,
+        iters: int("Iterations"),
+    })
+}
+
+pub fn to_kfr(camera: &Camera, formula: &Formula, iters: u32) -> String {
+    let log2_zoom = 2.0 - camera.zoom.log2_mult() - (camera.stretch().y as f64).log2();
+    // enough digits to land on the same pixel
+    let digits = (log2_zoom * LOG10_2).max(0.0) as usize + 10;
+    // it's a windows program, so crlf
+    let mut res = format!(
+        "Re: {:.*}\r\nIm: {:.*}\r\nZoom: {}\r\nIterations: {}\r\n",
+        digits,
+        camera.pos.x,
+        digits,
+        camera.pos.y,
+        scientific(log2_zoom),
+        iters
+    );
+    let kfr_type = match formula {
+        Formula::Multibrot(n) => Some((0, *n)),
+        f => KFR_TYPES.iter().find(|k| k.1 == *f).map(|k| (k.0, 2)),
+    };
+    if let Some((t, power)) = kfr_type {
+        res += &format!("FractalType: {}\r\nPower: {}\r\n", t, power);
+    }
+    res
+}
+
+fn parse_xpf(source: &str, mut camera: Camera) -> Result<Imported, String> {
+    let mut view = None;
+    let mut formula = None;
+    let mut iters = None;
+    // every command is (name args...), and none of the ones we need nest
+    for command in source.split('(').filter_map(|c| c.split(')').next()) {
+        let mut words = command.split_whitespace();
+        match words.next() {
+            Some("view") => view = Some(words.map(str::to_string).collect::<Vec<_>>()),
+            Some("maxiter") => iters = words.next().and_then(|s| s.parse().ok()),
+            Some("formula") => match words.next() {
+                Some("'mandel") => formula = Some(Formula::Mandelbrot),
+                Some(name) => {
+                    formula = name
+                        .strip_prefix("'mandel")
+                        .and_then(|n| n.parse().ok())
+                        .map(Formula::Multibrot)
+                }
+                None => (),
+            },
+            _ => (),
+        }
+    }
+    let view = view.ok_or("no view")?;
+    let [x, y, w, h] = view.as_slice() else {
+        return Err("view needs x, y, width, and height".to_string());
+    };
+    let num = |s: &String| {
+        s.parse::<FixedDec>()
+            .map_err(|e| format!("view '{}': {}", s, e))
+    };
+    camera.pos = Vector2::new(num(x)?, num(y)?);
+    // fit both sides like xaos does
+    let stretch = camera.stretch().cast::<f64>();
+    let log2_w = num(w)?.log2() - stretch.x.log2();
+    let log2_h = num(h)?.log2() - stretch.y.log2();
+    if !log2_w.is_finite() || !log2_h.is_finite() {
+        return Err("the view is empty".to_string());
+    }
+    camera.zoom = zoom_from_log2(log2_w.max(log2_h));
+    Ok(Imported {
+        camera,
+        formula,
+        iters,
+    })
+}
+
+pub fn to_xpf(camera: &Camera, formula: &Formula, iters: u32) -> String {
+    let stretch = camera.stretch().cast::<f64>();
+    let log2 = camera.zoom.log2_mult();
+    let digits = (-log2 * LOG10_2).max(0.0) as usize + 10;
+    let mut res = ";XaoS position file\n(initstate)\n".to_string();
+    match formula {
+        Formula::Mandelbrot => res += "(formula 'mandel)\n",
+        Formula::Multibrot(n) => res += &format!("(formula 'mandel{})\n", n),
+        _ => (),
+    }
+    res += &format!("(maxiter {})\n", iters);
+    res += &format!(
+        "(view {:.*} {:.*} {} {})\n",
+        digits,
+        camera.pos.x,
+        digits,
+        camera.pos.y,
+        scientific(log2 + stretch.x.log2()),
+        scientific(log2 + stretch.y.log2())
+    );
+    res
+}
+
+fn zoom_from_log2(log2_mult: f64) -> Zoom {
+    let level = (-log2_mult).round() as i32;
+    Zoom::new(level, (log2_mult + level as f64) as f32)
+}
+
+// 2^log2 as a decimal with an exponent, without going through an f64 that
+// could overflow
+fn scientific(log2: f64) -> String {
+    let log10 = log2 * LOG10_2;
+    let mut exp = log10.floor();
+    let mut mantissa = 10f64.powf(log10 - exp);
+    if format!("{:.9}", mantissa).starts_with("10") {
+        mantissa /= 10.0;
+        exp += 1.0;
+    }
+    format!("{:.9}E{}", mantissa, exp)
+}
@@ -0,0 +1,117 @@
+use nalgebra::Vector2;
+use num_traits::Zero;
+
+use crate::util::FixedDec;
+
+use super::{
+    parse_kfr, parse_xpf, to_kfr, to_xpf, zoom_from_log2, Camera, Formula, Imported, Zoom,
+};
+
+fn camera(x: &str, y: &str, level: i32, exp: f32, size: [u32; 2]) -> Camera {
+    Camera {
+        pos: Vector2::new(x.parse().unwrap(), y.parse().unwrap()),
+        zoom: Zoom::new(level, exp),
+        size: size.into(),
+    }
+}
+
+fn empty(size: Vector2<u32>) -> Camera {
+    Camera {
+        size,
+        ..Default::default()
+    }
+}
+
+// the same zoom, and a center within a thousandth of a pixel
+fn assert_same(a: &Camera, b: &Camera) {
+    assert_eq!(a.zoom.level(), b.zoom.level());
+    assert!((a.zoom.exp() - b.zoom.exp()).abs() < 1e-4);
+    let pixel = a.zoom.log2_mult() - (a.size.x.max(a.size.y) as f64).log2();
+    for (a, b) in a.pos.iter().zip(&b.pos) {
+        let diff = (a - b).log2();
+        assert!(diff < pixel - 10.0, "{} and {} are 2^{} apart", a, b, diff);
+    }
+}
+
+fn cameras() -> Vec<Camera> {
+    vec![
+        camera("-0.5", "0", 0, 0.2, [1920, 1080]),
+        camera("-0.75", "0.1015625", 3, -0.3, [500, 800]),
+        camera(
+            "-1.74995768370609350360221450607069970727110579726",
+            "0.00000000000000000000000000000000000000000000012345",
+            150,
+            0.45,
+            [640, 360],
+        ),
+        camera("0.25", "-0.5", -2, 0.0, [300, 300]),
+    ]
+}
+
+#[test]
+fn kfr_round_trip() {
+    for camera in cameras() {
+        let formula = Formula::Multibrot(3);
+        let source = to_kfr(&camera, &formula, 5000);
+        let Imported {
+            camera: back,
+            formula: back_formula,
+            iters,
+        } = parse_kfr(&source, empty(camera.size)).unwrap();
+        assert_same(&camera, &back);
+        assert_eq!(back_formula, Some(formula));
+        assert_eq!(iters, Some(5000));
+    }
+}
+
+#[test]
+fn xpf_round_trip() {
+    for camera in cameras() {
+        let source = to_xpf(&camera, &Formula::Mandelbrot, 300);
+        let Imported {
+            camera: back,
+            formula,
+            iters,
+        } = parse_xpf(&source, empty(camera.size)).unwrap();
+        assert_same(&camera, &back);
+        assert_eq!(formula, Some(Formula::Mandelbrot));
+        assert_eq!(iters, Some(300));
+    }
+}
+
+#[test]
+fn formulas_round_trip() {
+    let camera = &cameras()[0];
+    for formula in Formula::builtin() {
+        let source = to_kfr(camera, &formula, 100);
+        let back = parse_kfr(&source, empty(camera.size)).unwrap();
+        assert_eq!(back.formula, Some(formula));
+    }
+}
+
+#[test]
+fn zoom_log2() {
+    for log2 in [0.0, 0.49, -0.51, 1.0, -3.25, -300.7, 12.5] {
+        let zoom = zoom_from_log2(log2);
+        assert!(zoom.exp().abs() <= 0.5, "{}", zoom.exp());
+        assert!((zoom.log2_mult() - log2).abs() < 1e-6);
+    }
+}
+
+// a file laid out the way kalles fraktaler 2 saves them, crlf and all, from
+// a 640x360 window
+#[test]
+fn kfr_sample() {
+    let size = Vector2::new(640, 360);
+    let imported = parse_kfr(include_str!("sample.kfr"), empty(size)).unwrap();
+    assert_eq!(imported.formula, Some(Formula::Mandelbrot));
+    assert_eq!(imported.iters, Some(10000));
+    let re: FixedDec = "-1.74995768370609350360221450607069970727110579726252077930242837820286008082972804887218672784431700831100544507655659531379747541999999995"
+        .parse()
+        .unwrap();
+    assert_eq!(imported.camera.pos.x, re);
+    assert!(imported.camera.pos.y.is_zero());
+    // 4 / 1.2544e22 tall, and the width is 16 / 9 of that
+    let log2 = (4.0 / 1.2544e22 * 16.0 / 9.0f64).log2();
+    assert!((imported.camera.zoom.log2_mult() - log2).abs() < 1e-6);
+}
@@ -0,0 +1,34 @@
+use nalgebra::Vector2;
+
+use crate::util::FixedDec;
+
+use super::camera::Camera;
+
+// a julia set picked from a point in the mandelbrot view; whichever camera
+// isn't being shown is kept in other, so switching back and forth keeps both
+pub struct Julia {
+    pub c: Vector2<FixedDec>,
+    pub other: Camera,
+    pub active: bool,
+}
+
+impl Julia {
+    pub fn new(c: Vector2<FixedDec>, parent: Camera) -> Self {
+        Self {
+            c,
+            other: parent,
+            active: true,
+        }
+    }
+
+    pub fn c(&self) -> Option<&Vector2<FixedDec>> {
+        self.active.then_some(&self.c)
+    }
+
+    // swaps the shown camera with the other one
+    pub fn toggle(&mut self, camera: &mut Camera) {
+        self.other.size = camera.size;
+        std::mem::swap(camera, &mut self.other);
+        self.active = !self.active;
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use nalgebra::Vector2;
+
+use crate::util::FixedDec;
+
+use super::{
+    camera::{Camera, Zoom},
+    render::Formula,
+};
+
+#[cfg(test)]
+mod test;
+
+const PREFIX: &str = "fractal:v1:";
+
+// a place to share as one line of text,
+// fractal:v1:<x>:<y>:<zoom level>:<exp>[:formula[:<julia x>:<julia y>]]; x
+// and y are the exact fixed point parts in hex, and the formula is kept with
+// spaces as _. the formula is left empty for a julia set without one
+#[derive(Clone, Debug)]
+pub struct Location {
+    pub pos: Vector2<FixedDec>,
+    pub level: i32,
+    pub exp: f32,
+    pub formula: Option<String>,
+    pub julia: Option<Vector2<FixedDec>>,
+}
+
+impl Location {
+    pub fn new(camera: &Camera, formula: &Formula, julia: Option<&Vector2<FixedDec>>) -> Self {
+        Self {
+            pos: camera.pos.clone(),
+            level: camera.zoom.level(),
+            exp: camera.zoom.exp(),
+            formula: Some(encode(formula)),
+            julia: julia.cloned(),
+        }
+    }
+
+    pub fn camera(&self, size: Vector2<u32>) -> Camera {
+        Camera {
+            pos: self.pos.clone(),
+            zoom: Zoom::new(self.level, self.exp),
+            size,
+        }
+    }
+
+    pub fn find_formula(&self, formulas: &[Formula]) -> Option<usize> {
+        let name = self.formula.as_ref()?;
+        formulas.iter().position(|f| encode(f) == *name)
+    }
+}
+
+fn encode(formula: &Formula) -> String {
+    formula.to_string().replace(' ', "_")
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}:{}:{}:{}",
+            PREFIX,
+            self.pos.x.to_hex(),
+            self.pos.y.to_hex(),
+            self.level,
+            self.exp
+        )?;
+        if self.formula.is_some() || self.julia.is_some() {
+            write!(f, ":{}", self.formula.as_deref().unwrap_or_default())?;
+        }
+        if let Some(c) = &self.julia {
+            write!(f, ":{}:{}", c.x.to_hex(), c.y.to_hex())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Location {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some(rest) = s.strip_prefix(PREFIX) else {
+            if let Some(version) = s.strip_prefix("fractal:v") {
+                let version = version.split(':').next().unwrap_or_default();
+                return Err(format!("unknown location version v{}", version));
+            }
+            return Err(format!("locations start with '{}'", PREFIX));
+        };
+        let fields: Vec<_> = rest.split(':').collect();
+        let [x, y, level, exp, rest @ ..] = fields.as_slice() else {
+            return Err("expected x:y:level:exp after the version".to_string());
+        };
+        let pos = |s: &str| FixedDec::from_hex(s).map_err(|e| format!("'{}': {}", s, e));
+        let (formula, julia) = match rest {
+            [] => (None, None),
+            [formula] => (Some(formula), None),
+            [formula, jx, jy] => (Some(formula), Some(Vector2::new(pos(jx)?, pos(jy)?))),
+            [_, _] => return Err("expected both x and y of the julia c".to_string()),
+            _ => return Err("too many fields".to_string()),
+        };
+        Ok(Self {
+            pos: Vector2::new(pos(x)?, pos(y)?),
+            level: level
+                .parse()
+                .map_err(|_| format!("invalid zoom level '{}'", level))?,
+            exp: exp
+                .parse()
+                .map_err(|_| format!("invalid zoom exp '{}'", exp))?,
+            formula: formula.filter(|f| !f.is_empty()).map(|f| f.to_string()),
+            julia,
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use nalgebra::Vector2;
+
+use crate::util::FixedDec;
+
+use super::{
+    super::{
+        camera::{Camera, Zoom},
+        render::Formula,
+    },
+    Location,
+};
+
+fn camera() -> Camera {
+    Camera {
+        pos: Vector2::new(
+            "-1.74995768370609350360221450607069970727110579726"
+                .parse()
+                .unwrap(),
+            "0.00000000000000000000000000000000012345".parse().unwrap(),
+        ),
+        zoom: Zoom::new(150, -0.25),
+        size: Vector2::new(640, 360),
+    }
+}
+
+fn round_trip(location: &Location) -> Location {
+    let back: Location = location.to_string().parse().unwrap();
+    assert_eq!(back.pos, location.pos);
+    assert_eq!(back.level, location.level);
+    assert_eq!(back.exp, location.exp);
+    assert_eq!(back.formula, location.formula);
+    assert_eq!(back.julia, location.julia);
+    back
+}
+
+#[test]
+fn round_trips() {
+    round_trip(&Location::new(&camera(), &Formula::Mandelbrot, None));
+    let c = Vector2::new(FixedDec::from(-0.75), FixedDec::from(0.1015625));
+    let julia = round_trip(&Location::new(&camera(), &Formula::Tricorn, Some(&c)));
+    assert_eq!(julia.julia, Some(c.clone()));
+    // a julia set without a formula keeps its place empty
+    let location = Location {
+        formula: None,
+        ..Location::new(&camera(), &Formula::Mandelbrot, Some(&c))
+    };
+    round_trip(&location);
+    round_trip(&Location {
+        formula: None,
+        ..Location::new(&camera(), &Formula::Mandelbrot, None)
+    });
+}
+
+#[test]
+fn formula_names() {
+    let formulas = Formula::builtin();
+    let location = Location::new(&camera(), &Formula::BurningShip, None);
+    assert!(location.to_string().ends_with(":burning_ship"));
+    let back = round_trip(&location);
+    let i = back.find_formula(&formulas).unwrap();
+    assert_eq!(formulas[i], Formula::BurningShip);
+    let multibrot = round_trip(&Location::new(&camera(), &Formula::Multibrot(3), None));
+    assert_eq!(multibrot.formula.as_deref(), Some("multibrot_3"));
+}
+
+#[test]
+fn errors() {
+    let good = Location::new(&camera(), &Formula::Mandelbrot, None).to_string();
+    let fields: Vec<_> = good.split(':').collect();
+    let with = |i: usize, field: &str| {
+        let mut fields = fields.clone();
+        fields[i] = field;
+        fields.join(":").parse::<Location>().err().unwrap()
+    };
+    assert!(with(2, "x").starts_with("'x'"));
+    assert!(with(3, "").starts_with("''"));
+    assert_eq!(with(4, "deep"), "invalid zoom level 'deep'");
+    assert_eq!(with(5, "half"), "invalid zoom exp 'half'");
+    assert_eq!(with(1, "v9"), "unknown location version v9");
+    let err = |s: &str| s.parse::<Location>().err().unwrap();
+    assert_eq!(err("0.5:0:1:0"), "locations start with 'fractal:v1:'");
+    assert_eq!(
+        err("fractal:v1:0.8:0:1"),
+        "expected x:y:level:exp after the version"
+    );
+    assert_eq!(
+        err(&format!("{}:0.8", good)),
+        "expected both x and y of the julia c"
+    );
+    assert_eq!(err(&format!("{}:0.8:0.8:0.8", good)), "too many fields");
+}
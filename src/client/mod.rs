@@ -1,21 +1,43 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
+use arboard::Clipboard;
+use auto_snapshot::AutoSnapshot;
+use bookmark::Bookmarks;
 use camera::Camera;
+use checkpoint::Checkpoint;
 use handle_input::InputHandling;
 use input::Input;
-use render::Renderer;
+use julia::Julia;
+use render::{Renderer, SnapshotKind};
+use watch::Watcher;
 use winit::{
+    dpi::PhysicalSize,
     event::WindowEvent,
     window::{Window, WindowAttributes},
 };
 
 mod app;
+mod auto_snapshot;
+mod bookmark;
 mod camera;
+mod checkpoint;
 mod handle_input;
+mod headless;
 mod input;
+mod interop;
+mod julia;
+mod location;
 mod render;
+mod watch;
 
 pub use app::*;
+pub use checkpoint::default_path as default_checkpoint;
+pub use headless::*;
+pub use location::Location;
 
 pub struct Client<'a> {
     window: Arc<Window>,
@@ -25,24 +47,179 @@ pub struct Client<'a> {
     prev_update: Instant,
     renderer: Renderer<'a>,
     handling: InputHandling,
+    julia: Option<Julia>,
+    watcher: Option<Watcher>,
+    bookmarks: Bookmarks,
+    clipboard: Option<Clipboard>,
+    auto_snapshot: AutoSnapshot,
+    // the most iterations any pixel had done at the last progress read back,
+    // which exports go out with since the viewer itself has no limit
+    iters: u32,
 }
 
 impl Client<'_> {
-    pub fn new(event_loop: &winit::event_loop::ActiveEventLoop) -> Self {
+    pub fn new(
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        watch: Option<PathBuf>,
+        location: Option<&Location>,
+        open: Option<&Path>,
+        resume: Option<&Path>,
+    ) -> Self {
+        let checkpoint = resume.and_then(|path| {
+            Checkpoint::read(path)
+                .map_err(|e| println!("{}: {}", path.display(), e))
+                .ok()
+        });
+        let mut attributes = WindowAttributes::default();
+        if let Some(checkpoint) = &checkpoint {
+            // the work buffer only fits a window the same size
+            let size = checkpoint.size;
+            attributes = attributes.with_inner_size(PhysicalSize::new(size.x, size.y));
+        }
         let window = Arc::new(
             event_loop
-                .create_window(WindowAttributes::default())
+                .create_window(attributes)
                 .expect("failed to create window"),
         );
         let renderer = Renderer::new(window.clone());
-        Self {
+        let mut client = Self {
             window,
-            camera: Camera::default(),
+            camera: Camera {
+                size: *renderer.size(),
+                ..Default::default()
+            },
             input: Input::new(),
             exit: false,
             prev_update: Instant::now(),
             renderer,
             handling: InputHandling::new(),
+            julia: None,
+            watcher: watch.map(Watcher::new),
+            bookmarks: Bookmarks::load(),
+            clipboard: Clipboard::new()
+                .map_err(|e| println!("no clipboard, locations only get printed: {}", e))
+                .ok(),
+            auto_snapshot: AutoSnapshot::default(),
+            iters: 0,
+        };
+        if let Some(location) = location {
+            handle_input::go_to(
+                location,
+                &mut client.camera,
+                &mut client.handling,
+                &mut client.julia,
+            );
+        }
+        if let Some(path) = open {
+            client.open(path);
+        }
+        if let Some(checkpoint) = checkpoint {
+            client.resume(checkpoint);
+        }
+        client
+    }
+
+    // saves everything iterated so far for --resume to carry on from
+    pub fn save_checkpoint(&self) {
+        let Some(path) = checkpoint::default_path() else {
+            println!("no cache dir to save a checkpoint to");
+            return;
+        };
+        let checkpoint = Checkpoint {
+            location: Location::new(
+                &self.camera,
+                self.handling.formula(),
+                self.julia.as_ref().and_then(Julia::c),
+            ),
+            formula: checkpoint::formula_hash(self.handling.formula()),
+            julia: self.julia.as_ref().and_then(Julia::c).cloned(),
+            size: self.camera.size,
+            state: self.renderer.save_work(),
+        };
+        match checkpoint.write(&path) {
+            Ok(()) => println!("saved checkpoint to {}", path.display()),
+            Err(e) => println!("could not save checkpoint: {}", e),
+        }
+    }
+
+    fn resume(&mut self, checkpoint: Checkpoint) {
+        // the work is only good for the formula it was iterated with, which
+        // the view bytes restore checks don't cover
+        let name = checkpoint.location.formula.as_deref().unwrap_or("none");
+        let Some(i) = checkpoint.location.find_formula(&self.handling.formulas) else {
+            println!("could not resume, no formula '{}'", name);
+            return;
+        };
+        if checkpoint::formula_hash(&self.handling.formulas[i]) != checkpoint.formula {
+            println!(
+                "could not resume, '{}' has changed since the checkpoint",
+                name
+            );
+            return;
+        }
+        handle_input::go_to(
+            &checkpoint.location,
+            &mut self.camera,
+            &mut self.handling,
+            &mut self.julia,
+        );
+        if let Some(c) = checkpoint.julia {
+            self.julia = Some(Julia::new(c, Camera::default()));
+        }
+        self.handling.mode = checkpoint.state.mode;
+        let julia = self.julia.as_ref().and_then(Julia::c);
+        match self.renderer.restore_work(
+            &self.camera,
+            self.handling.formula(),
+            julia,
+            checkpoint.state,
+        ) {
+            Ok(()) => println!("resumed from the checkpoint"),
+            Err(e) => println!("could not resume, starting over: {}", e),
+        }
+    }
+
+    // goes to the view in a .kfr or .xpf file
+    pub fn open(&mut self, path: &Path) {
+        let imported = match interop::import(path, self.camera.size) {
+            Ok(imported) => imported,
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+        self.camera = imported.camera;
+        self.julia = None;
+        if let Some(formula) = imported.formula {
+            match self.handling.formulas.iter().position(|f| *f == formula) {
+                Some(i) => self.handling.formula = i,
+                None => println!(
+                    "no formula {}, staying on {}",
+                    formula,
+                    self.handling.formula()
+                ),
+            }
+        }
+        println!("opened {}", path.display());
+    }
+
+    // writes the view to the first free fractal-N.kfr and .xpf in the
+    // working dir
+    pub fn export(&self) {
+        let formula = self.handling.formula();
+        let name = (1..)
+            .map(|i| format!("fractal-{}", i))
+            .find(|n| !Path::new(&format!("{}.kfr", n)).exists())
+            .unwrap();
+        for (ext, source) in [
+            ("kfr", interop::to_kfr(&self.camera, formula, self.iters)),
+            ("xpf", interop::to_xpf(&self.camera, formula, self.iters)),
+        ] {
+            let path = format!("{}.{}", name, ext);
+            match std::fs::write(&path, source) {
+                Ok(()) => println!("wrote {}", path),
+                Err(e) => println!("could not write {}: {}", path, e),
+            }
         }
     }
 
@@ -51,6 +228,14 @@ impl Client<'_> {
             event_loop.exit();
         }
 
+        if let Some(watcher) = &mut self.watcher {
+            if watcher.poll() {
+                println!("reloading {}", watcher.dir().display());
+                self.handling.reload_formulas(watcher.dir());
+                self.renderer.reload_shaders(watcher.dir());
+            }
+        }
+
         let now = Instant::now();
         self.handle_input(now - self.prev_update);
         self.input.end();
@@ -61,13 +246,29 @@ impl Client<'_> {
     pub fn window_event(&mut self, event: WindowEvent) {
         match event {
             WindowEvent::CloseRequested => self.exit = true,
+            WindowEvent::DroppedFile(ref path) => self.open(path),
             WindowEvent::Resized(size) => {
                 self.renderer.resize(size);
                 self.camera.size = *self.renderer.size();
             }
             WindowEvent::RedrawRequested => {
-                self.renderer.render(&self.camera, self.handling.snapshot);
-                self.handling.snapshot = false;
+                if let Some(progress) = self.renderer.progress() {
+                    self.iters = progress.iters;
+                    let pixels = self.camera.size.x * self.camera.size.y;
+                    let level = self.camera.zoom.level();
+                    if self.auto_snapshot.update(progress, pixels, level) {
+                        self.handling.snapshot.get_or_insert(SnapshotKind::Auto);
+                    }
+                }
+                self.renderer.render(
+                    &self.camera,
+                    self.handling.snapshot,
+                    self.handling.mode,
+                    self.handling.formula(),
+                    self.julia.as_ref().and_then(Julia::c),
+                    &self.handling.coloring,
+                );
+                self.handling.snapshot = None;
                 self.window.request_redraw();
             }
             _ => (),
@@ -1,6 +1,7 @@
 use nalgebra::Vector2;
+use num_traits::Zero;
 
-use crate::util::FixedDec;
+use crate::util::{FixedDec, FloatExp};
 
 use super::Camera;
 
@@ -19,28 +20,49 @@ impl ComputeView {
 impl Default for ComputeView {
     fn default() -> Self {
         let val = FixedDec::from_parts(false, 0, vec![0, 0, 0]);
-        Self::new(true, Vector2::zeros(), Vector2::zeros(), 0, &val, &val, &val)
+        Self::new(
+            true,
+            Vector2::zeros(),
+            Vector2::zeros(),
+            0,
+            FloatExp::ZERO,
+            &val,
+            &val,
+            &val,
+            false,
+            &val,
+            &val,
+        )
     }
 }
 
 impl ComputeView {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         reset: bool,
         dims: Vector2<u32>,
         stretch: Vector2<f32>,
         level: i32,
+        fe_scale: FloatExp,
         scale: &FixedDec,
         x: &FixedDec,
         y: &FixedDec,
+        julia: bool,
+        julia_x: &FixedDec,
+        julia_y: &FixedDec,
     ) -> Self {
         let mut bytes = Vec::new();
         bytes.extend((reset as u32).to_le_bytes());
         bytes.extend(level.to_le_bytes());
         bytes.extend(bytemuck::cast_slice(&[dims.x, dims.y]));
         bytes.extend(bytemuck::cast_slice(&[stretch.x, stretch.y]));
+        fe_scale.to_bytes(&mut bytes);
         scale.to_bytes(&mut bytes);
         x.to_bytes(&mut bytes);
         y.to_bytes(&mut bytes);
+        bytes.extend((julia as u32).to_le_bytes());
+        julia_x.to_bytes(&mut bytes);
+        julia_y.to_bytes(&mut bytes);
         let rem = bytes.len() % VIEW_ALIGN;
         if rem != 0 {
             bytes.extend((0..(VIEW_ALIGN - rem)).map(|_| 0));
@@ -48,19 +70,40 @@ impl ComputeView {
         Self { bytes }
     }
 
-    pub fn from_camera(camera: &Camera, reset: bool, len: usize) -> Self {
-        let mut x = camera.pos.x.clone();
-        x.set_whole_len(1);
-        x.set_dec_len(len as i32 - 1);
-        let mut y = camera.pos.y.clone();
-        y.set_whole_len(1);
-        y.set_dec_len(len as i32 - 1);
+    // julia is the constant c when drawing a julia set instead of the mandelbrot
+    pub fn from_camera(
+        camera: &Camera,
+        reset: bool,
+        len: usize,
+        julia: Option<&Vector2<FixedDec>>,
+    ) -> Self {
+        let fit = |v: &FixedDec| v.clone().with_lens(1, len as i32 - 1);
+        let x = fit(&camera.pos.x);
+        let y = fit(&camera.pos.y);
+        let zero = fit(&FixedDec::zero());
+        let (julia_x, julia_y) = match julia {
+            Some(c) => (fit(&c.x), fit(&c.y)),
+            None => (zero.clone(), zero),
+        };
 
         let stretch = camera.stretch();
+        let fe_scale = FloatExp::from(camera.zoom.mult());
         let mut scale = camera.zoom.mult().clone();
         scale.set_precision(len);
 
-        Self::new(reset, camera.size, stretch, camera.zoom.level(), &scale, &x, &y)
+        Self::new(
+            reset,
+            camera.size,
+            stretch,
+            camera.zoom.level(),
+            fe_scale,
+            &scale,
+            &x,
+            &y,
+            julia.is_some(),
+            &julia_x,
+            &julia_y,
+        )
     }
 }
 
@@ -69,3 +112,33 @@ impl PartialEq for ComputeView {
         self.bytes[1..] == other.bytes[1..]
     }
 }
+
+// the histogram of escape iterations has one bin per iteration up to
+// 2^HIST_BITS, then 2^HIST_BITS bins for each doubling, like a float's
+// mantissa, so the narrow band that everything escapes in at deep zooms is
+// still spread over plenty of them. HIST_BITS and hist_bin are also in
+// shader.wgsl, perturb.wgsl and color.wgsl
+pub const HIST_BITS: u32 = 14;
+pub const HIST_LEN: usize = (33 - HIST_BITS as usize) << HIST_BITS;
+
+// what the compute shaders leave for the coloring pass for each pixel, like
+// the Raw struct in shader.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Raw {
+    pub escaped: u32,
+    // hasn't escaped, but the distance estimate put it within half a pixel
+    pub done: u32,
+    pub iters: u32,
+    // the normalized iteration count
+    pub normalized: f32,
+    // |z| at the first iteration past the bailout
+    pub abs_z: f32,
+    // exterior distance estimate in pixels, negative when there isn't one
+    pub de: f32,
+    // unit z / dz, which points away from the set
+    pub normal: [f32; 2],
+}
+
+unsafe impl bytemuck::Pod for Raw {}
+unsafe impl bytemuck::Zeroable for Raw {}
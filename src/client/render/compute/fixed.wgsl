@@ -1,3 +1,8 @@
+const LEN: u32 = REPLACE_LENu;
+const ILEN: i32 = i32(LEN);
+const LEN1: u32 = LEN + 1;
+const LEN2: u32 = LEN * 2;
+
 const POS: u32 = 0u;
 const NEG: u32 = 1u;
 
@@ -135,6 +140,99 @@ fn mul(lhs: FixedDec, rhs: FixedDec) -> FixedDec {
     return FixedDec(sign, dec, new_parts);
 }
 
+// exact square root truncated to LEN parts, same as FixedDec::sqrt(LEN)
+fn sqrt(x: FixedDec) -> FixedDec {
+    var parts = x.parts;
+    var start = 0u;
+    while start < LEN && parts[start] == 0 {
+        start += 1u;
+    }
+    if start == LEN {
+        return zero();
+    }
+    var dec = x.dec - i32(start);
+    var rad = array<u32, LEN2>();
+    var k = 0u;
+    if rem_euclid(dec, 2) == 1 {
+        k = 1u;
+        dec += 1;
+    }
+    for (var i = start; i < LEN; i += 1u) {
+        rad[k] = parts[i];
+        k += 1u;
+    }
+
+    // digit by digit, rem < 2 * root + 1 so it needs one more part than root
+    var root = array<u32, LEN>();
+    var rem = array<u32, LEN1>();
+    for (var i = 0u; i < LEN * 32u; i += 1u) {
+        let bits = (rad[i / 16u] >> (30u - (i % 16u) * 2u)) & 3u;
+        var carry = bits;
+        var j = LEN1;
+        while j > 0u {
+            j -= 1u;
+            let next = rem[j] >> 30u;
+            rem[j] = (rem[j] << 2u) | carry;
+            carry = next;
+        }
+        var trial = array<u32, LEN1>();
+        carry = 1u;
+        j = LEN;
+        while j > 0u {
+            j -= 1u;
+            trial[j + 1u] = (root[j] << 2u) | carry;
+            carry = root[j] >> 30u;
+        }
+        trial[0] = carry;
+        var ge = true;
+        for (j = 0u; j < LEN1; j += 1u) {
+            if rem[j] != trial[j] {
+                ge = rem[j] > trial[j];
+                break;
+            }
+        }
+        if ge {
+            var borrow = false;
+            j = LEN1;
+            while j > 0u {
+                j -= 1u;
+                let a = rem[j];
+                let res = a - trial[j] - u32(borrow);
+                rem[j] = res;
+                borrow = a < trial[j] || (a == trial[j] && borrow);
+            }
+        }
+        carry = u32(ge);
+        j = LEN;
+        while j > 0u {
+            j -= 1u;
+            let next = root[j] >> 31u;
+            root[j] = (root[j] << 1u) | carry;
+            carry = next;
+        }
+    }
+    return FixedDec(POS, dec / 2, root);
+}
+
+// same steps as FixedDec::powi, but every mul is truncated to LEN parts,
+// so it only matches powi exactly when the result fits in LEN parts; bit for
+// bit it's wgsl::pow, which is powi with each product truncated
+fn pow(x: FixedDec, n: u32) -> FixedDec {
+    var res = one();
+    var base = x;
+    var exp = n;
+    while exp > 0u {
+        if (exp & 1u) == 1u {
+            res = mul(res, base);
+        }
+        exp >>= 1u;
+        if exp > 0u {
+            base = mul(base, base);
+        }
+    }
+    return res;
+}
+
 fn gt(x: FixedDec, y: FixedDec) -> bool {
     if x.dec > y.dec {
         return true;
@@ -271,6 +369,12 @@ fn zero() -> FixedDec {
     return FixedDec(0, 0, array<u32, LEN>());
 }
 
+fn one() -> FixedDec {
+    var parts = array<u32, LEN>();
+    parts[0] = 1u;
+    return FixedDec(POS, 1, parts);
+}
+
 fn div_euclid(x: i32, y: i32) -> i32 {
     if x < 0 {
         return -((-x - 1) / y) - 1;
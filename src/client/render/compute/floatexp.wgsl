@@ -0,0 +1,126 @@
+// float with an extended exponent, value = mant * 2^exp; mant is kept in
+// [0.5, 1) like frexp, same as FloatExp on the rust side but with an f32 mantissa
+struct FloatExp {
+    mant: f32,
+    exp: i32,
+}
+
+// past this difference in exponents the smaller value can't change the f32 sum
+const FE_MAX_EXP_DIFF: i32 = 32;
+
+fn fe_new(mant: f32, exp: i32) -> FloatExp {
+    if mant == 0.0 {
+        return FloatExp(0.0, 0);
+    }
+    let f = frexp(mant);
+    return FloatExp(f.fract, exp + f.exp);
+}
+
+fn fe_from_f32(x: f32) -> FloatExp {
+    return fe_new(x, 0);
+}
+
+fn fe_to_f32(x: FloatExp) -> f32 {
+    // ldexp is undefined outside of the f32 range, so clamp by hand
+    if x.mant == 0.0 || x.exp < -149 {
+        return 0.0 * x.mant;
+    }
+    if x.exp > 128 {
+        return sign(x.mant) * 3.4028235e38;
+    }
+    return ldexp(x.mant, x.exp);
+}
+
+fn fe_from_fixed(x: FixedDec) -> FloatExp {
+    var parts = x.parts;
+    var k = 0;
+    while k < ILEN && parts[k] == 0 {
+        k += 1;
+    }
+    if k == ILEN {
+        return FloatExp(0.0, 0);
+    }
+    let lz = countLeadingZeros(parts[k]);
+    var top = parts[k] << lz;
+    if lz != 0 && k + 1 < ILEN {
+        top |= parts[k + 1] >> (32u - lz);
+    }
+    let exp = 32 * (x.dec - k) - i32(lz);
+    var mant = ldexp(f32(top), -32);
+    if x.sign == NEG {
+        mant = -mant;
+    }
+    return fe_new(mant, exp);
+}
+
+fn fe_add(x: FloatExp, y: FloatExp) -> FloatExp {
+    if x.mant == 0.0 {
+        return y;
+    }
+    if y.mant == 0.0 {
+        return x;
+    }
+    let diff = x.exp - y.exp;
+    if diff > FE_MAX_EXP_DIFF {
+        return x;
+    }
+    if diff < -FE_MAX_EXP_DIFF {
+        return y;
+    }
+    if diff >= 0 {
+        return fe_new(x.mant + ldexp(y.mant, -diff), x.exp);
+    }
+    return fe_new(ldexp(x.mant, diff) + y.mant, y.exp);
+}
+
+fn fe_neg(x: FloatExp) -> FloatExp {
+    return FloatExp(-x.mant, x.exp);
+}
+
+fn fe_sub(x: FloatExp, y: FloatExp) -> FloatExp {
+    return fe_add(x, fe_neg(y));
+}
+
+fn fe_mul(x: FloatExp, y: FloatExp) -> FloatExp {
+    return fe_new(x.mant * y.mant, x.exp + y.exp);
+}
+
+fn fe_div(x: FloatExp, y: FloatExp) -> FloatExp {
+    return fe_new(x.mant / y.mant, x.exp - y.exp);
+}
+
+fn fe_sqr(x: FloatExp) -> FloatExp {
+    return fe_mul(x, x);
+}
+
+// |x + iy|, scaled so the squares can't overflow
+fn fe_length(x: FloatExp, y: FloatExp) -> FloatExp {
+    let diff = x.exp - y.exp;
+    if y.mant == 0.0 || diff > FE_MAX_EXP_DIFF {
+        return FloatExp(abs(x.mant), x.exp);
+    }
+    if x.mant == 0.0 || diff < -FE_MAX_EXP_DIFF {
+        return FloatExp(abs(y.mant), y.exp);
+    }
+    let exp = max(x.exp, y.exp);
+    return fe_new(length(vec2(ldexp(x.mant, x.exp - exp), ldexp(y.mant, y.exp - exp))), exp);
+}
+
+fn fe_mul_pow2(x: FloatExp, exp: i32) -> FloatExp {
+    if x.mant == 0.0 {
+        return x;
+    }
+    return FloatExp(x.mant, x.exp + exp);
+}
+
+fn fe_gt(x: FloatExp, y: FloatExp) -> bool {
+    let xneg = x.mant < 0.0;
+    let yneg = y.mant < 0.0;
+    if xneg != yneg {
+        return yneg;
+    }
+    if x.mant == 0.0 || y.mant == 0.0 || x.exp == y.exp {
+        return x.mant > y.mant;
+    }
+    return (x.exp > y.exp) != xneg;
+}
@@ -0,0 +1,194 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+use crate::util::Script;
+
+// the iteration step of the escape time loop; generates the wgsl that goes in
+// place of REPLACE_BAILOUT and REPLACE_FORMULA in shader.wgsl, where x, y,
+// x2 = x*x, y2 = y*y, cx, and cy are in scope. the bailout has to define
+// escaped, and the body needs to set x and y to the next value
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum Formula {
+    #[default]
+    Mandelbrot,
+    BurningShip,
+    Tricorn,
+    Celtic,
+    Buffalo,
+    Multibrot(u32),
+    Script {
+        name: String,
+        script: Script,
+    },
+}
+
+pub const MIN_POWER: u32 = 3;
+pub const MAX_POWER: u32 = 8;
+
+impl Formula {
+    pub fn builtin() -> Vec<Self> {
+        let mut res = vec![
+            Self::Mandelbrot,
+            Self::BurningShip,
+            Self::Tricorn,
+            Self::Celtic,
+            Self::Buffalo,
+        ];
+        res.extend((MIN_POWER..=MAX_POWER).map(Self::Multibrot));
+        res
+    }
+
+    // every .formula script in dir, sorted by name; the ones that don't
+    // compile are skipped with their error printed
+    pub fn load_dir(dir: &Path) -> Vec<Self> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "formula"))
+            .collect();
+        paths.sort();
+        paths.iter().filter_map(|p| Self::load(p)).collect()
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                println!("{}: {}", path.display(), e);
+                return None;
+            }
+        };
+        match source.parse() {
+            Ok(script) => Some(Self::Script {
+                name: path.file_stem()?.to_string_lossy().into_owned(),
+                script,
+            }),
+            Err(e) => {
+                println!("{}:{}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    // the reference orbit and perturb.wgsl only know z^2 + c
+    pub fn perturbable(&self) -> bool {
+        *self == Self::Mandelbrot
+    }
+
+    // the power of z, which smooth coloring needs; scripts are treated as
+    // quadratic since there's no telling what they do
+    pub fn degree(&self) -> u32 {
+        match self {
+            Self::Multibrot(n) => *n,
+            _ => 2,
+        }
+    }
+
+    // the ones where the shader can track dz/dc for the distance estimate,
+    // which needs the derivative to be DEGREE * z^(DEGREE - 1)
+    pub fn differentiable(&self) -> bool {
+        matches!(self, Self::Mandelbrot | Self::Multibrot(_))
+    }
+
+    pub fn bailout(&self) -> String {
+        match self {
+            Self::Script { script, .. } => script.bailout.clone(),
+            _ => "
+        let escaped = gt(add(x2, y2), thresh);"
+                .to_string(),
+        }
+    }
+
+    pub fn body(&self) -> String {
+        match self {
+            Self::Mandelbrot => "
+        let xy = mul(x, y);
+        y = add(add(xy, xy), cy);
+        x = add(sub(x2, y2), cx);"
+                .to_string(),
+            Self::BurningShip => "
+        var xy = mul(x, y);
+        xy.sign = POS;
+        y = add(add(xy, xy), cy);
+        x = add(sub(x2, y2), cx);"
+                .to_string(),
+            Self::Tricorn => "
+        let xy = mul(x, y);
+        y = sub(cy, add(xy, xy));
+        x = add(sub(x2, y2), cx);"
+                .to_string(),
+            Self::Celtic => "
+        let xy = mul(x, y);
+        var re = sub(x2, y2);
+        re.sign = POS;
+        y = add(add(xy, xy), cy);
+        x = add(re, cx);"
+                .to_string(),
+            Self::Buffalo => "
+        var xy = mul(x, y);
+        xy.sign = POS;
+        var re = sub(x2, y2);
+        re.sign = POS;
+        y = add(add(xy, xy), cy);
+        x = add(re, cx);"
+                .to_string(),
+            Self::Multibrot(n) => multibrot(*n),
+            Self::Script { script, .. } => script.body.clone(),
+        }
+    }
+}
+
+// expands (x + iy)^n with the binomial theorem, so the real part gets the
+// even powers of y and the imaginary part the odd ones, alternating signs
+fn multibrot(n: u32) -> String {
+    let mut body = String::new();
+    for k in 3..=n {
+        body += &format!("\n        let x{k} = mul(x{}, x);", k - 1);
+        body += &format!("\n        let y{k} = mul(y{}, y);", k - 1);
+    }
+    let power = |v: &str, k: u32| match k {
+        0 => None,
+        1 => Some(v.to_string()),
+        k => Some(format!("{v}{k}")),
+    };
+    let mut re = "cx".to_string();
+    let mut im = "cy".to_string();
+    let mut coef = 1u32;
+    for k in 0..=n {
+        let term = match (power("x", n - k), power("y", k)) {
+            (Some(a), Some(b)) => format!("mul({a}, {b})"),
+            (Some(a), None) | (None, Some(a)) => a,
+            (None, None) => unreachable!(),
+        };
+        let term = match coef {
+            1 => term,
+            c => format!("mul(from_f32({c}.0), {term})"),
+        };
+        let (acc, neg) = if k % 2 == 0 {
+            (&mut re, k % 4 == 2)
+        } else {
+            (&mut im, k % 4 == 3)
+        };
+        *acc = format!("{}({acc}, {term})", if neg { "sub" } else { "add" });
+        coef = coef * (n - k) / (k + 1);
+    }
+    body + &format!("\n        let re = {re};\n        y = {im};\n        x = re;")
+}
+
+impl Display for Formula {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mandelbrot => write!(f, "mandelbrot"),
+            Self::BurningShip => write!(f, "burning ship"),
+            Self::Tricorn => write!(f, "tricorn"),
+            Self::Celtic => write!(f, "celtic"),
+            Self::Buffalo => write!(f, "buffalo"),
+            Self::Multibrot(n) => write!(f, "multibrot {n}"),
+            Self::Script { name, .. } => write!(f, "{name}"),
+        }
+    }
+}
@@ -1,50 +1,45 @@
-use wgpu::{PipelineCompilationOptions, ShaderStages};
+use std::collections::HashMap;
+
+use nalgebra::Vector2;
+use wgpu::PipelineCompilationOptions;
 
 use crate::client::render::util::ArrayBuffer;
 
-use super::{
-    util::{Storage, Texture},
-    ComputeView,
-};
+use super::{util::Storage, ComputeView, ProgressBuffer, Raw, HIST_LEN};
 
 pub struct Layout {
     bind_layout: wgpu::BindGroupLayout,
     pipeline_layout: wgpu::PipelineLayout,
-    pub output: Texture,
     pub view: Storage,
     pub work: ArrayBuffer<u32>,
+    pub orbit: ArrayBuffer<[f32; 2]>,
+    pub series: ArrayBuffer<u32>,
+    pub progress: ProgressBuffer,
+    pub raw: Storage,
+    pub histogram: Storage,
 }
 
 impl Layout {
-    pub fn init(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, len: usize) -> Self {
+    pub fn init(device: &wgpu::Device, size: Vector2<u32>, len: usize) -> Self {
         let view = Storage::init_with(device, "view", ComputeView::default().bytes());
         let work = ArrayBuffer::init_with(
             device,
             "test",
             wgpu::BufferUsages::STORAGE,
-            &work_vec(config.width, config.height, len),
+            &work_vec(size.x, size.y, len),
         );
 
-        let desc = wgpu::TextureDescriptor {
-            label: Some("compute output"),
-            size: wgpu::Extent3d {
-                width: config.width,
-                height: config.height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
-            view_formats: &[],
-        };
-        let output = Texture::init(
+        let orbit = ArrayBuffer::init_with(
             device,
-            desc,
-            wgpu::TextureViewDescriptor::default(),
-            wgpu::SamplerDescriptor::default(),
+            "reference orbit",
+            wgpu::BufferUsages::STORAGE,
+            &[[0.0, 0.0]],
         );
+        let series = ArrayBuffer::init_with(device, "series", wgpu::BufferUsages::STORAGE, &[0; 6]);
+        let progress = ProgressBuffer::init(device);
+
+        let raw = Storage::init_with(device, "raw", &raw_vec(size));
+        let histogram = Storage::init_with(device, "histogram", &vec![0u8; HIST_LEN * 4]);
 
         let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
@@ -54,16 +49,19 @@ impl Layout {
                     wgpu::BufferBindingType::Storage { read_only: false },
                     wgpu::ShaderStages::COMPUTE,
                 ),
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::StorageTexture {
-                        access: wgpu::StorageTextureAccess::WriteOnly,
-                        format: output.format(),
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                    },
-                    count: None,
-                },
+                raw.bind_group_layout_entry(2, false, wgpu::ShaderStages::COMPUTE),
+                orbit.bind_group_layout_entry(
+                    3,
+                    wgpu::BufferBindingType::Storage { read_only: true },
+                    wgpu::ShaderStages::COMPUTE,
+                ),
+                series.bind_group_layout_entry(
+                    4,
+                    wgpu::BufferBindingType::Storage { read_only: true },
+                    wgpu::ShaderStages::COMPUTE,
+                ),
+                progress.bind_group_layout_entry(5),
+                histogram.bind_group_layout_entry(6, false, wgpu::ShaderStages::COMPUTE),
             ],
             label: Some("compute"),
         });
@@ -76,10 +74,14 @@ impl Layout {
 
         Self {
             view,
-            output,
             bind_layout,
             pipeline_layout,
             work,
+            orbit,
+            series,
+            progress,
+            raw,
+            histogram,
         }
     }
 
@@ -89,7 +91,11 @@ impl Layout {
             entries: &[
                 self.view.bind_group_entry(0),
                 self.work.bind_group_entry(1),
-                self.output.view_bind_group_entry(2),
+                self.raw.bind_group_entry(2),
+                self.orbit.bind_group_entry(3),
+                self.series.bind_group_entry(4),
+                self.progress.bind_group_entry(5),
+                self.histogram.bind_group_entry(6),
             ],
             label: Some("voxel render"),
         })
@@ -99,6 +105,7 @@ impl Layout {
         &self,
         device: &wgpu::Device,
         shader: &wgpu::ShaderModule,
+        constants: &HashMap<String, f64>,
     ) -> wgpu::ComputePipeline {
         device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: Some("Voxel Pipeline"),
@@ -106,16 +113,26 @@ impl Layout {
             entry_point: Some("main"),
             module: shader,
             cache: None,
-            compilation_options: PipelineCompilationOptions::default(),
+            compilation_options: PipelineCompilationOptions {
+                constants,
+                ..Default::default()
+            },
         })
     }
 }
 
+// i, then x and y as sign, dec, and len parts, then dz/dc as two FloatExp,
+// like shader.wgsl; perturb.wgsl uses less than that
 pub fn work_size(width: u32, height: u32, len: usize) -> usize {
-    let varwidth = (2 + len) * 2;
-    (width * height) as usize * (varwidth * 2 + 1)
+    let varwidth = 2 + len;
+    (width * height) as usize * (varwidth * 2 + 5)
 }
 
 pub fn work_vec(width: u32, height: u32, len: usize) -> Vec<u32> {
     vec![0u32; work_size(width, height, len)]
 }
+
+// zeroed raws for every pixel, in the same order as the work
+pub fn raw_vec(size: Vector2<u32>) -> Vec<u8> {
+    vec![0; (size.x * size.y) as usize * std::mem::size_of::<Raw>()]
+}
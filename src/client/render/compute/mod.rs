@@ -1,11 +1,25 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+};
+
+use crate::util::{FixedDec, FloatExp};
 
 mod data;
+mod formula;
 mod layout;
+mod progress;
+mod reference;
+mod series;
+mod source;
 
 use super::*;
 pub use data::*;
+pub use formula::*;
 use layout::*;
+pub use progress::*;
+use reference::*;
+pub use source::*;
 
 pub struct ComputePipeline {
     layout: Layout,
@@ -13,23 +27,81 @@ pub struct ComputePipeline {
     bind_group: wgpu::BindGroup,
     old_view: ComputeView,
     old_len: usize,
+    size: Vector2<u32>,
+    mode: ComputeMode,
+    formula: Formula,
+    // the raw buffer and histogram need clearing before the next pass, like
+    // after restoring work they weren't counted with
+    clear_raw: bool,
+    reference: Option<ReferenceOrbit>,
+    sources: Sources,
+    sources_changed: bool,
+    // the len, mode and formula of the last shader that didn't compile
+    failed: Option<(usize, ComputeMode, Formula)>,
+    // a new len in perturbation mode, printed along with its series skip
+    announce_len: bool,
 }
 
-const FIXED_SHADER: &str = include_str!("fixed.wgsl");
-const SHADER: &str = include_str!("shader.wgsl");
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ComputeMode {
+    #[default]
+    Direct,
+    Perturbation,
+}
+
+impl ComputeMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Direct => Self::Perturbation,
+            Self::Perturbation => Self::Direct,
+        }
+    }
+}
+
+// what the compute shader has done so far; the work buffer only means
+// anything with the view bytes it was iterated with
+pub struct WorkState {
+    pub mode: ComputeMode,
+    pub len: usize,
+    pub view: Vec<u8>,
+    pub work: Vec<u32>,
+}
+
+// how much of the reference orbit gets computed each frame, and the most
+// it'll ever have; pixels past the end just rebase onto the start. the series
+// is a lot more expensive, so it goes slower while that's still being computed
+const ORBIT_STEP: usize = 2000;
+const SERIES_STEP: usize = 100;
+const MAX_ORBIT: usize = 1 << 16;
+// WGX and WGY in the shaders
+const WORKGROUP_SIZE: u32 = 8;
 
 impl ComputePipeline {
-    pub fn init(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, len: usize) -> Self {
-        let layout = Layout::init(device, config, len);
+    pub fn init(device: &wgpu::Device, size: Vector2<u32>, len: usize) -> Self {
+        let layout = Layout::init(device, size, len);
+        let mode = ComputeMode::default();
+        let formula = Formula::default();
+        let sources = Sources::builtin();
+        let shader = Self::shader(device, &sources, len, mode, &formula);
         Self {
-            pipeline: layout.pipeline(device, &Self::shader(device, len)),
+            pipeline: layout.pipeline(device, &shader, &constants(&formula)),
             bind_group: layout.bind_group(device),
             layout,
             old_view: ComputeView::default(),
             old_len: len,
+            size,
+            mode,
+            formula,
+            clear_raw: false,
+            reference: None,
+            sources,
+            sources_changed: false,
+            failed: None,
+            announce_len: false,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         device: &wgpu::Device,
@@ -37,20 +109,64 @@ impl ComputePipeline {
         belt: &mut wgpu::util::StagingBelt,
         camera: &Camera,
         len: usize,
+        mode: ComputeMode,
+        formula: &Formula,
+        julia: Option<&Vector2<FixedDec>>,
     ) {
-        let mut view = ComputeView::from_camera(camera, false, len);
-        if view != self.old_view {
+        let mode = if formula.perturbable() && julia.is_none() {
+            mode
+        } else {
+            ComputeMode::Direct
+        };
+        let changed = mode != self.mode || *formula != self.formula || self.sources_changed;
+        let mut rebuilt = false;
+        if len != self.old_len || changed {
+            // a shader that didn't compile isn't tried again until something
+            // about it changes, and the last one keeps going meanwhile
+            let attempt = (len, mode, formula.clone());
+            if self.sources_changed || self.failed.as_ref() != Some(&attempt) {
+                self.sources_changed = false;
+                if self.rebuild(device, len, mode, formula) {
+                    // with perturbation the len goes out once the series skip
+                    // for it is known
+                    if len != self.old_len {
+                        if mode == ComputeMode::Direct {
+                            println!("new len: {}", len);
+                        } else {
+                            self.announce_len = true;
+                        }
+                    }
+                    self.old_len = len;
+                    self.mode = mode;
+                    self.formula = formula.clone();
+                    self.work.set(work_vec(camera.size.x, camera.size.y, len));
+                    self.failed = None;
+                    rebuilt = true;
+                } else {
+                    self.failed = Some(attempt);
+                }
+            }
+        }
+        let (len, mode) = (self.old_len, self.mode);
+        let mut view = ComputeView::from_camera(camera, false, len, julia);
+        if view != self.old_view || rebuilt {
             for (i, b) in 1u32.to_le_bytes().iter().enumerate() {
                 view.bytes[i] = *b;
             }
+            self.clear_raw = true;
+        }
+        // pixels go in the histogram the first frame they're escaped in raw
+        if self.clear_raw {
+            self.raw.clear(encoder);
+            self.histogram.clear(encoder);
+            self.clear_raw = false;
         }
-        if len != self.old_len {
-            println!("new len: {}", len);
-            self.old_len = len;
-            self.pipeline = self.pipeline(device, &Self::shader(device, len));
-            self.work.set(work_vec(camera.size.x, camera.size.y, len));
+        if mode == ComputeMode::Perturbation {
+            self.update_reference(camera, len);
         }
         let updated = self.work.update(device, encoder, belt)
+            | self.orbit.update(device, encoder, belt)
+            | self.series.update(device, encoder, belt)
             | self.view.update(device, encoder, belt, view.bytes());
         if updated {
             self.bind_group = self.layout.bind_group(device);
@@ -59,35 +175,171 @@ impl ComputePipeline {
     }
 
     pub fn run(&self, encoder: &mut wgpu::CommandEncoder) {
+        self.progress.clear(encoder);
         let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
         pass.set_pipeline(&self.pipeline);
         pass.set_bind_group(0, &self.bind_group, &[]);
-        pass.dispatch_workgroups(240, 135, 1);
+        pass.dispatch_workgroups(
+            self.size.x.div_ceil(WORKGROUP_SIZE),
+            self.size.y.div_ceil(WORKGROUP_SIZE),
+            1,
+        );
     }
 
-    pub fn resize(&mut self, device: &wgpu::Device, size: Vector2<u32>, len: usize) {
+    // keeps the len the current pipeline was built for
+    pub fn resize(&mut self, device: &wgpu::Device, size: Vector2<u32>) {
+        self.size = size;
+        let len = self.old_len;
         self.work.set(work_vec(size.x, size.y, len));
-        self.old_len = len;
-        self.output.resize(
-            device,
-            wgpu::Extent3d {
-                width: size.x,
-                height: size.y,
-                depth_or_array_layers: 1,
-            },
-        );
+        self.raw = util::Storage::init_with(device, "raw", &raw_vec(size));
         self.bind_group = self.layout.bind_group(device);
     }
 
-    pub fn shader(device: &wgpu::Device, len: usize) -> wgpu::ShaderModule {
-        let string = FIXED_SHADER.to_string() + &SHADER.replace("REPLACE_LEN", &format!("{}", len));
+    pub fn save_work(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> WorkState {
+        WorkState {
+            mode: self.mode,
+            len: self.old_len,
+            view: self.old_view.bytes.clone(),
+            work: self.work.read(device, queue),
+        }
+    }
+
+    // carries on from a saved state instead of starting over, as long as
+    // it's for the view the camera is at
+    pub fn restore(
+        &mut self,
+        device: &wgpu::Device,
+        camera: &Camera,
+        formula: &Formula,
+        julia: Option<&Vector2<FixedDec>>,
+        state: WorkState,
+    ) -> Result<(), String> {
+        let view = ComputeView { bytes: state.view };
+        if view != ComputeView::from_camera(camera, false, state.len, julia) {
+            return Err("it's for a different view or window size".to_string());
+        }
+        if state.work.len() != work_size(camera.size.x, camera.size.y, state.len) {
+            return Err("the work buffer is the wrong size".to_string());
+        }
+        if !self.rebuild(device, state.len, state.mode, formula) {
+            return Err("the shader didn't compile".to_string());
+        }
+        self.old_len = state.len;
+        self.mode = state.mode;
+        self.formula = formula.clone();
+        self.sources_changed = false;
+        self.failed = None;
+        self.work.set(state.work);
+        self.old_view = view;
+        self.clear_raw = true;
+        if state.mode == ComputeMode::Perturbation {
+            // pixels can be anywhere along the orbit, so all of it has to be
+            // there before they carry on
+            let (x, y) = (&camera.pos.x, &camera.pos.y);
+            let mut reference = ReferenceOrbit::new(x, y, state.len, Self::radius(camera));
+            reference.extend(MAX_ORBIT, MAX_ORBIT);
+            self.layout.orbit.set(reference.orbit.clone());
+            self.layout.series.set(reference.series.to_words());
+            self.reference = Some(reference);
+        }
+        Ok(())
+    }
+
+    fn radius(camera: &Camera) -> FloatExp {
+        FloatExp::from(camera.zoom.mult()) * FloatExp::from(camera.stretch().norm() / 2.0)
+    }
+
+    fn update_reference(&mut self, camera: &Camera, len: usize) {
+        let (x, y) = (&camera.pos.x, &camera.pos.y);
+        let radius = Self::radius(camera);
+        // only the part of the orbit that's new since last frame goes up
+        let (reference, start) = match &mut self.reference {
+            Some(r) if r.is_for(x, y, len, radius) => {
+                let start = r.orbit.len();
+                (r, start)
+            }
+            r => (r.insert(ReferenceOrbit::new(x, y, len, radius)), 0),
+        };
+        let series_done = reference.series.is_done();
+        let step = if series_done { ORBIT_STEP } else { SERIES_STEP };
+        if reference.extend(step, MAX_ORBIT) || start == 0 {
+            let orbit = &mut self.layout.orbit;
+            orbit.set_from(start, reference.orbit[start..].to_vec());
+            if !series_done {
+                self.layout.series.set(reference.series.to_words());
+            }
+        }
+        if self.announce_len && reference.series.is_done() {
+            let series = &reference.series;
+            println!(
+                "new len: {}, skipped iters: {} ({} terms)",
+                len,
+                series.skip,
+                series.terms()
+            );
+            self.announce_len = false;
+        }
+    }
+
+    pub fn work_bytes(size: Vector2<u32>, len: usize) -> u64 {
+        (work_size(size.x, size.y, len) * std::mem::size_of::<u32>()) as u64
+    }
+
+    pub fn set_sources(&mut self, sources: Sources) {
+        self.sources = sources;
+        self.sources_changed = true;
+    }
+
+    // keeps the last pipeline if the new one doesn't compile, which can only
+    // really happen with shaders from a watched directory; returns whether it
+    // did, so the len, mode and formula only change along with the pipeline
+    fn rebuild(
+        &mut self,
+        device: &wgpu::Device,
+        len: usize,
+        mode: ComputeMode,
+        formula: &Formula,
+    ) -> bool {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = Self::shader(device, &self.sources, len, mode, formula);
+        let pipeline = self.pipeline(device, &shader, &constants(formula));
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(e) => {
+                println!("shader error, keeping the last pipeline:\n{}", e);
+                false
+            }
+            None => {
+                self.pipeline = pipeline;
+                true
+            }
+        }
+    }
+
+    pub fn shader(
+        device: &wgpu::Device,
+        sources: &Sources,
+        len: usize,
+        mode: ComputeMode,
+        formula: &Formula,
+    ) -> wgpu::ShaderModule {
         device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("compute"),
-            source: wgpu::ShaderSource::Wgsl(string.into()),
+            source: wgpu::ShaderSource::Wgsl(sources.assemble(len, mode, formula).into()),
         })
     }
 }
 
+// the overrides the shaders leave to the pipeline
+fn constants(formula: &Formula) -> HashMap<String, f64> {
+    HashMap::from([
+        ("DEGREE".to_string(), formula.degree() as f64),
+        (
+            "DERIVATIVE".to_string(),
+            formula.differentiable() as u32 as f64,
+        ),
+    ])
+}
+
 impl Deref for ComputePipeline {
     type Target = Layout;
 
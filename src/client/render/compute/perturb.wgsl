@@ -0,0 +1,231 @@
+override WGX: u32 = 8;
+override WGY: u32 = 8;
+// perturbation is cheap enough to do a bunch of iterations each frame
+override ITERS: u32 = 64;
+// the same as in shader.wgsl
+override DEGREE: f32 = 2.0;
+// always z^2 + c here, but the client sets it for both shaders
+override DERIVATIVE: bool = true;
+const BAILOUT: f32 = 128.0;
+const DONE_DIST: f32 = 0.5;
+const DONE_RADIUS: f32 = 16.0;
+// the same as in data.rs
+const HIST_BITS: u32 = 14u;
+// squared Pauldelbot tolerance; if |z| gets this small relative to the
+// reference |Z|, the low precision delta can't be trusted anymore
+const GLITCH_TOL: f32 = 1e-6;
+
+struct View {
+    reset: u32,
+    level: i32,
+    dims: vec2<u32>,
+    stretch: vec2<f32>,
+    fe_scale: FloatExp,
+    scale: FixedDec,
+    corner_x: FixedDec,
+    corner_y: FixedDec,
+}
+
+@group(0) @binding(0)
+var<storage> view: View;
+@group(0) @binding(1)
+var<storage, read_write> work: array<u32>;
+@group(0) @binding(2)
+var<storage, read_write> raw: array<Raw>;
+@group(0) @binding(3)
+var<storage> orbit: array<vec2<f32>>;
+@group(0) @binding(4)
+var<storage> series: Series;
+@group(0) @binding(5)
+var<storage, read_write> progress: Progress;
+@group(0) @binding(6)
+var<storage, read_write> histogram: array<atomic<u32>>;
+
+// the same as in shader.wgsl
+struct Raw {
+    escaped: u32,
+    done: u32,
+    iters: u32,
+    normalized: f32,
+    abs_z: f32,
+    de: f32,
+    normal: vec2<f32>,
+}
+
+// cleared every frame and read back by the client for auto snapshots
+struct Progress {
+    undecided: atomic<u32>,
+    iters: atomic<u32>,
+}
+
+struct Coef {
+    x: FloatExp,
+    y: FloatExp,
+}
+
+// series approximation d_skip = a_1 * dc + a_2 * dc^2 + ..., from the cpu
+struct Series {
+    skip: u32,
+    terms: u32,
+    coefs: array<Coef>,
+}
+
+// each pixel is z = Z_m + d, where Z is the reference orbit computed on the cpu
+// for the center of the view; only d is iterated here, in FloatExp:
+// d' = 2 * Z_m * d + d^2 + dc
+// along with the derivative of the whole z, dz' = 2 * z * dz + 1
+@compute @workgroup_size(WGX, WGY, 1)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>
+) {
+    if id.x > view.dims.x - 1 || id.y > view.dims.y - 1 {
+        return;
+    }
+    // i, m, then d and dz/dc as FloatExp
+    let workwidth = 10u;
+    let worki = (id.x * view.dims.y + id.y) * workwidth;
+
+    let fdims = vec2<f32>(view.dims);
+    let fpos = (vec2<f32>(id.xy) / fdims - 0.5) * view.stretch;
+    let dcx = fe_mul(fe_from_f32(fpos.x), view.fe_scale);
+    let dcy = fe_mul(fe_from_f32(fpos.y), view.fe_scale);
+    var i = work[worki];
+    var m = work[worki + 1];
+    var dx: FloatExp;
+    var dy: FloatExp;
+    var dzx: FloatExp;
+    var dzy: FloatExp;
+    if bool(view.reset) {
+        i = 0u;
+        m = 0u;
+        dx = FloatExp(0.0, 0);
+        dy = FloatExp(0.0, 0);
+        dzx = FloatExp(0.0, 0);
+        dzy = FloatExp(0.0, 0);
+    } else {
+        dx = FloatExp(bitcast<f32>(work[worki + 2]), bitcast<i32>(work[worki + 3]));
+        dy = FloatExp(bitcast<f32>(work[worki + 4]), bitcast<i32>(work[worki + 5]));
+        dzx = FloatExp(bitcast<f32>(work[worki + 6]), bitcast<i32>(work[worki + 7]));
+        dzy = FloatExp(bitcast<f32>(work[worki + 8]), bitcast<i32>(work[worki + 9]));
+    }
+    let orbit_len = arrayLength(&orbit);
+    // the series can get further along while pixels are iterating, so
+    // anything behind it that hasn't escaped jumps ahead
+    if i < series.skip && series.skip < orbit_len {
+        let z = orbit[m] + vec2(fe_to_f32(dx), fe_to_f32(dy));
+        if dot(z, z) <= 4.0 {
+            var sx = FloatExp(0.0, 0);
+            var sy = FloatExp(0.0, 0);
+            // the derivative of s, so dz/dc = s + dc * s'
+            var tx = FloatExp(0.0, 0);
+            var ty = FloatExp(0.0, 0);
+            var k = series.terms;
+            while k > 0u {
+                k -= 1u;
+                let coef = series.coefs[k];
+                let ntx = fe_add(fe_sub(fe_mul(tx, dcx), fe_mul(ty, dcy)), sx);
+                ty = fe_add(fe_add(fe_mul(tx, dcy), fe_mul(ty, dcx)), sy);
+                tx = ntx;
+                let nx = fe_sub(fe_mul(sx, dcx), fe_mul(sy, dcy));
+                let ny = fe_add(fe_mul(sx, dcy), fe_mul(sy, dcx));
+                sx = fe_add(nx, coef.x);
+                sy = fe_add(ny, coef.y);
+            }
+            dx = fe_sub(fe_mul(sx, dcx), fe_mul(sy, dcy));
+            dy = fe_add(fe_mul(sx, dcy), fe_mul(sy, dcx));
+            dzx = fe_add(sx, fe_sub(fe_mul(tx, dcx), fe_mul(ty, dcy)));
+            dzy = fe_add(sy, fe_add(fe_mul(tx, dcy), fe_mul(ty, dcx)));
+            i = series.skip;
+            m = series.skip;
+        }
+    }
+    let max = i + ITERS;
+    var escaped = false;
+    var z = vec2(0.0, 0.0);
+    loop {
+        var zr = orbit[m];
+        let d = vec2(fe_to_f32(dx), fe_to_f32(dy));
+        z = zr + d;
+        let r2 = dot(z, z);
+        if r2 > BAILOUT * BAILOUT {
+            escaped = true;
+            break;
+        }
+        if i >= max {
+            break;
+        }
+        let wx = fe_from_f32(2.0 * z.x);
+        let wy = fe_from_f32(2.0 * z.y);
+        let nzx = fe_sub(fe_mul(wx, dzx), fe_mul(wy, dzy));
+        dzy = fe_add(fe_mul(wx, dzy), fe_mul(wy, dzx));
+        dzx = fe_add(nzx, FloatExp(0.5, 1));
+        // rebase onto the start of the orbit when the pixel gets closer to 0
+        // than the reference is (Zhuoran), on a glitch, or when the orbit runs out
+        let glitch = r2 < GLITCH_TOL * dot(zr, zr);
+        if m + 1 >= orbit_len || r2 < dot(d, d) || glitch {
+            dx = fe_add(fe_from_f32(zr.x), dx);
+            dy = fe_add(fe_from_f32(zr.y), dy);
+            m = 0u;
+            zr = orbit[0];
+        }
+        let zx = fe_from_f32(zr.x);
+        let zy = fe_from_f32(zr.y);
+        let lin_x = fe_mul_pow2(fe_sub(fe_mul(zx, dx), fe_mul(zy, dy)), 1);
+        let lin_y = fe_mul_pow2(fe_add(fe_mul(zx, dy), fe_mul(zy, dx)), 1);
+        let nx = fe_add(fe_add(lin_x, fe_sub(fe_sqr(dx), fe_sqr(dy))), dcx);
+        let ny = fe_add(fe_add(lin_y, fe_mul_pow2(fe_mul(dx, dy), 1)), dcy);
+        dx = nx;
+        dy = ny;
+        m += 1u;
+        i += 1u;
+    }
+    work[worki] = i;
+    atomicMax(&progress.iters, i);
+    work[worki + 1] = m;
+    work[worki + 2] = bitcast<u32>(dx.mant); work[worki + 3] = bitcast<u32>(dx.exp);
+    work[worki + 4] = bitcast<u32>(dy.mant); work[worki + 5] = bitcast<u32>(dy.exp);
+    work[worki + 6] = bitcast<u32>(dzx.mant); work[worki + 7] = bitcast<u32>(dzx.exp);
+    work[worki + 8] = bitcast<u32>(dzy.mant); work[worki + 9] = bitcast<u32>(dzy.exp);
+    let rawi = id.x * view.dims.y + id.y;
+    let prev = raw[rawi];
+    let est = estimate(z, dzx, dzy);
+    var out = Raw(0u, 0u, i, f32(i), 0.0, est.x, est.yz);
+    if escaped {
+        out.escaped = 1u;
+        out.abs_z = length(z);
+        out.normalized += 1.0 - log(log(out.abs_z)) / log(DEGREE);
+        if prev.escaped == 0u {
+            atomicAdd(&histogram[min(hist_bin(i), arrayLength(&histogram) - 1u)], 1u);
+        }
+    } else if length(z) > DONE_RADIUS && est.x >= 0.0 && est.x < DONE_DIST {
+        out.done = 1u;
+    }
+    if out.escaped == 0u && out.done == 0u {
+        atomicAdd(&progress.undecided, 1u);
+    }
+    raw[rawi] = out;
+}
+
+// the same as in shader.wgsl
+fn estimate(z: vec2<f32>, dzx: FloatExp, dzy: FloatExp) -> vec3<f32> {
+    let abs_z = length(z);
+    let abs_dz = fe_length(dzx, dzy);
+    if abs_z <= 1.0 || abs_dz.mant == 0.0 {
+        return vec3(-1.0, 0.0, 0.0);
+    }
+    let pixel = fe_mul(view.fe_scale, fe_from_f32(view.stretch.x / f32(view.dims.x)));
+    let de = fe_to_f32(fe_div(fe_from_f32(abs_z * log(abs_z)), fe_mul(abs_dz, pixel)));
+    let d = vec2(fe_to_f32(fe_div(dzx, abs_dz)), fe_to_f32(fe_div(dzy, abs_dz)));
+    let u = vec2(z.x * d.x + z.y * d.y, z.y * d.x - z.x * d.y);
+    return vec3(de, u / length(u));
+}
+
+// the same as in shader.wgsl
+fn hist_bin(i: u32) -> u32 {
+    if i < (1u << HIST_BITS) {
+        return i;
+    }
+    let e = firstLeadingBit(i);
+    let mant = (i >> (e - HIST_BITS)) & ((1u << HIST_BITS) - 1u);
+    return ((e - HIST_BITS + 1u) << HIST_BITS) + mant;
+}
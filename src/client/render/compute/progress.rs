@@ -0,0 +1,114 @@
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+
+// counted by the compute shader every frame, like the Progress struct in
+// shader.wgsl and perturb.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Progress {
+    // pixels that haven't escaped yet
+    pub undecided: u32,
+    // the most iterations any pixel has done
+    pub iters: u32,
+}
+
+unsafe impl bytemuck::Pod for Progress {}
+unsafe impl bytemuck::Zeroable for Progress {}
+
+// the counts are copied out and read back a frame or so later, without
+// waiting on the gpu for them
+pub struct ProgressBuffer {
+    buffer: wgpu::Buffer,
+    map_buf: wgpu::Buffer,
+    // set while map_buf is being mapped, it can't be copied into until it's read
+    pending: Option<Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    copied: bool,
+}
+
+impl ProgressBuffer {
+    pub fn init(device: &wgpu::Device) -> Self {
+        let size = std::mem::size_of::<Progress>() as u64;
+        Self {
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("progress"),
+                size,
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            map_buf: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("progress readback"),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            pending: None,
+            copied: false,
+        }
+    }
+
+    // before the compute pass
+    pub fn clear(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.clear_buffer(&self.buffer, 0, None);
+    }
+
+    // after the compute pass
+    pub fn copy(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.pending.is_some() || self.copied {
+            return;
+        }
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &self.map_buf, 0, self.map_buf.size());
+        self.copied = true;
+    }
+
+    // after the copy is submitted
+    pub fn map(&mut self) {
+        if !self.copied {
+            return;
+        }
+        self.copied = false;
+        let (s, r) = channel();
+        self.map_buf
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |res| {
+                let _ = s.send(res);
+            });
+        self.pending = Some(r);
+    }
+
+    // the counts from the last copy if it's been mapped by now
+    pub fn read(&mut self) -> Option<Progress> {
+        let res = match self.pending.as_ref()?.try_recv() {
+            Ok(res) => res,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err(wgpu::BufferAsyncError),
+        };
+        self.pending = None;
+        res.ok()?;
+        let data = self.map_buf.slice(..).get_mapped_range();
+        let progress = *bytemuck::from_bytes(&data);
+        drop(data);
+        self.map_buf.unmap();
+        Some(progress)
+    }
+
+    pub fn bind_group_layout_entry(&self, binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    }
+
+    pub fn bind_group_entry(&self, binding: u32) -> wgpu::BindGroupEntry {
+        wgpu::BindGroupEntry {
+            binding,
+            resource: self.buffer.as_entire_binding(),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::util::{FixedDec, FloatExp};
+
+use super::series::SeriesApprox;
+
+// reference orbit for perturbation; Z_n is iterated at full precision on the
+// cpu for a single point, and the gpu only iterates each pixel's difference
+// from it. it's extended a bit every frame since the renderer never stops
+pub struct ReferenceOrbit {
+    cx: FixedDec,
+    cy: FixedDec,
+    x: FixedDec,
+    y: FixedDec,
+    len: usize,
+    escaped: bool,
+    radius: FloatExp,
+    pub orbit: Vec<[f32; 2]>,
+    pub series: SeriesApprox,
+}
+
+impl ReferenceOrbit {
+    // radius is the largest distance from c to a pixel, for the series
+    pub fn new(cx: &FixedDec, cy: &FixedDec, len: usize, radius: FloatExp) -> Self {
+        Self {
+            cx: cx.clone(),
+            cy: cy.clone(),
+            x: FixedDec::zeros(),
+            y: FixedDec::zeros(),
+            len,
+            escaped: false,
+            radius,
+            orbit: vec![[0.0, 0.0]],
+            series: SeriesApprox::new(radius, len),
+        }
+    }
+
+    pub fn is_for(&self, cx: &FixedDec, cy: &FixedDec, len: usize, radius: FloatExp) -> bool {
+        self.len == len && self.radius == radius && &self.cx == cx && &self.cy == cy
+    }
+
+    // returns whether anything was added; the series is stepped along with
+    // the orbit since it needs every Z_n at full precision
+    pub fn extend(&mut self, iters: usize, max_len: usize) -> bool {
+        let start = self.orbit.len();
+        while !self.escaped && self.orbit.len() < max_len && self.orbit.len() - start < iters {
+            self.series.step(&self.x, &self.y);
+            let x2 = &self.x * &self.x;
+            let y2 = &self.y * &self.y;
+            let xy = &self.x * &self.y;
+            self.y = &xy + &xy;
+            self.y += &self.cy;
+            self.x = x2 - y2;
+            self.x += &self.cx;
+            for v in [&mut self.x, &mut self.y] {
+                v.set_whole_len(1);
+                v.set_dec_len(self.len as i32 - 1);
+            }
+            let z = [f32::from(&self.x), f32::from(&self.y)];
+            self.escaped = z[0] * z[0] + z[1] * z[1] > 4.0;
+            self.orbit.push(z);
+        }
+        if self.escaped || self.orbit.len() >= max_len {
+            self.series.finish();
+        }
+        self.orbit.len() != start
+    }
+}
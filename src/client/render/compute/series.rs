@@ -0,0 +1,130 @@
+use crate::util::{FixedDec, FloatExp};
+
+// most terms the series will use; one more is computed for the error bound
+pub const MAX_TERMS: usize = 6;
+// the first term that isn't used has to be this small relative to the last
+// one that is, at the edge of the screen, for the series to be trusted
+const TOL: f64 = 1e-4;
+
+type Complex = (FixedDec, FixedDec);
+
+// univariate series approximation of the perturbation delta,
+// d_n = a_1 * dc + a_2 * dc^2 + ...; the coefficients only depend on the
+// reference orbit, so every pixel can start at iteration skip instead of 0
+pub struct SeriesApprox {
+    coefs: Vec<Complex>,
+    valid: Vec<bool>,
+    radius2: FloatExp,
+    len: usize,
+    n: usize,
+    pub skip: usize,
+    pub best: Vec<[FloatExp; 2]>,
+}
+
+impl SeriesApprox {
+    // radius is the largest |dc| on screen
+    pub fn new(radius: FloatExp, len: usize) -> Self {
+        Self {
+            coefs: vec![(FixedDec::zeros(), FixedDec::zeros()); MAX_TERMS + 1],
+            valid: vec![true; MAX_TERMS],
+            radius2: radius.sqr(),
+            len,
+            n: 0,
+            skip: 0,
+            best: Vec::new(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        !self.valid.iter().any(|&v| v)
+    }
+
+    pub fn terms(&self) -> usize {
+        self.best.len()
+    }
+
+    pub fn finish(&mut self) {
+        self.valid.fill(false);
+    }
+
+    // advances the coefficients with the reference value z = Z_n:
+    // a_1' = 2 Z a_1 + 1, a_k' = 2 Z a_k + sum(a_i a_j, i + j = k);
+    // returns whether skip changed
+    pub fn step(&mut self, zx: &FixedDec, zy: &FixedDec) -> bool {
+        if self.is_done() {
+            return false;
+        }
+        let z2 = (zx + zx, zy + zy);
+        let mut next: Vec<Complex> = self.coefs.iter().map(|a| mul(&z2, a)).collect();
+        next[0].0 += FixedDec::one();
+        for (k, a) in next.iter_mut().enumerate().skip(1) {
+            for i in 0..k {
+                let j = k - 1 - i;
+                if i > j {
+                    break;
+                }
+                let mut prod = mul(&self.coefs[i], &self.coefs[j]);
+                if i != j {
+                    prod = (&prod.0 + &prod.0, &prod.1 + &prod.1);
+                }
+                a.0 += prod.0;
+                a.1 += prod.1;
+            }
+        }
+        // leading zero parts would use up the precision otherwise
+        for (x, y) in &mut next {
+            x.trim();
+            y.trim();
+            x.set_precision(self.len);
+            y.set_precision(self.len);
+        }
+        self.coefs = next;
+        self.n += 1;
+
+        let mags: Vec<FloatExp> = self
+            .coefs
+            .iter()
+            .map(|(x, y)| FloatExp::from(x).sqr() + FloatExp::from(y).sqr())
+            .collect();
+        let tol2 = FloatExp::from(TOL * TOL);
+        for k in 0..MAX_TERMS {
+            if self.valid[k] && mags[k + 1] * self.radius2 > tol2 * mags[k] {
+                self.valid[k] = false;
+            }
+        }
+        // valid[k] means the terms after k are still negligible, so the most
+        // terms that are valid is the most accurate truncation
+        let Some(terms) = self.valid.iter().rposition(|&v| v) else {
+            return false;
+        };
+        self.skip = self.n;
+        self.best = self.coefs[..=terms]
+            .iter()
+            .map(|(x, y)| [FloatExp::from(x), FloatExp::from(y)])
+            .collect();
+        true
+    }
+
+    // layout of the series buffer in perturb.wgsl
+    pub fn to_words(&self) -> Vec<u32> {
+        let mut bytes = Vec::new();
+        bytes.extend((self.skip as u32).to_le_bytes());
+        bytes.extend((self.terms() as u32).to_le_bytes());
+        for [x, y] in &self.best {
+            x.to_bytes(&mut bytes);
+            y.to_bytes(&mut bytes);
+        }
+        if self.best.is_empty() {
+            // the runtime sized array can't be empty
+            bytes.extend([0; 16]);
+        }
+        bytes
+            .chunks(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+}
+
+fn mul(a: &Complex, b: &Complex) -> Complex {
+    (&a.0 * &b.0 - &a.1 * &b.1, &a.0 * &b.1 + &a.1 * &b.0)
+}
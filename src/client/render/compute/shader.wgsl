@@ -1,18 +1,34 @@
 override CHUNK_POW: u32 = 10;
-const LEN: u32 = REPLACE_LENu;
-const ILEN: i32 = i32(LEN);
-const LEN2: u32 = LEN * 2;
 override WGX: u32 = 8;
 override WGY: u32 = 8;
+// set by the client, the power of the formula for the normalized iteration
+// count
+override DEGREE: f32 = 2.0;
+// set by the client for the formulas whose derivative is DEGREE * z^(DEGREE - 1)
+override DERIVATIVE: bool = true;
+// big enough that the normalized iteration count doesn't show bands
+const BAILOUT: f32 = 128.0;
+// pixels that haven't escaped but are closer than this to the set, in pixels,
+// won't look any different however long they take
+const DONE_DIST: f32 = 0.5;
+// the distance estimate is only close once z is this far out, it says next to
+// nothing while the orbit is still wandering around near the set
+const DONE_RADIUS: f32 = 16.0;
+// the same as in data.rs
+const HIST_BITS: u32 = 14u;
 
 struct View {
     reset: u32,
     level: i32,
     dims: vec2<u32>,
     stretch: vec2<f32>,
+    fe_scale: FloatExp,
     scale: FixedDec,
     corner_x: FixedDec,
     corner_y: FixedDec,
+    julia: u32,
+    julia_x: FixedDec,
+    julia_y: FixedDec,
 }
 
 @group(0) @binding(0)
@@ -20,7 +36,37 @@ var<storage> view: View;
 @group(0) @binding(1)
 var<storage, read_write> work: array<u32>;
 @group(0) @binding(2)
-var output: texture_storage_2d<rgba8unorm, write>;
+var<storage, read_write> raw: array<Raw>;
+@group(0) @binding(5)
+var<storage, read_write> progress: Progress;
+// how many pixels escaped at each iteration, for equalized coloring. cleared
+// along with raw whenever the work starts over
+@group(0) @binding(6)
+var<storage, read_write> histogram: array<atomic<u32>>;
+
+// what the coloring pass in output/color.wgsl works from, rewritten every
+// frame
+struct Raw {
+    escaped: u32,
+    // hasn't escaped, but z is past DONE_RADIUS and the distance estimate puts
+    // it within DONE_DIST
+    done: u32,
+    iters: u32,
+    // the normalized iteration count
+    normalized: f32,
+    // |z| at the first iteration past the bailout
+    abs_z: f32,
+    // exterior distance estimate in pixels, negative when there isn't one
+    de: f32,
+    // unit z / dz, which points away from the set
+    normal: vec2<f32>,
+}
+
+// cleared every frame and read back by the client for auto snapshots
+struct Progress {
+    undecided: atomic<u32>,
+    iters: atomic<u32>,
+}
 
 @compute @workgroup_size(WGX, WGY, 1)
 fn main(
@@ -30,21 +76,42 @@ fn main(
         return;
     }
     let varwidth = LEN + 2;
-    let workwidth = varwidth * 2 + 1;
+    // dz/dc is two FloatExp after x and y
+    let workwidth = varwidth * 2 + 5;
     let worki = (id.x * view.dims.y + id.y) * workwidth;
     let xidx = worki + 1;
     let yidx = xidx + varwidth;
+    let dzidx = yidx + varwidth;
 
     let fdims = vec2<f32>(view.dims);
     let fpos = (vec2<f32>(id.xy) / fdims - 0.5) * view.stretch;
-    let cx = add(mul(from_f32(fpos.x), view.scale), view.corner_x);
-    let cy = add(mul(from_f32(fpos.y), view.scale), view.corner_y);
+    let px = add(mul(from_f32(fpos.x), view.scale), view.corner_x);
+    let py = add(mul(from_f32(fpos.y), view.scale), view.corner_y);
+    // the mandelbrot set starts at 0 with c as the pixel, a julia set starts
+    // at the pixel with a constant c
+    var cx = px;
+    var cy = py;
+    if bool(view.julia) {
+        cx = view.julia_x;
+        cy = view.julia_y;
+    }
     var x: FixedDec;
     var y: FixedDec;
+    // for a julia set it's dz/dz0 instead
+    var dzx: FloatExp;
+    var dzy: FloatExp;
     var i = work[worki];
     if bool(view.reset) {
-        x = zero();
-        y = zero();
+        if bool(view.julia) {
+            x = px;
+            y = py;
+            dzx = FloatExp(0.5, 1);
+        } else {
+            x = zero();
+            y = zero();
+            dzx = FloatExp(0.0, 0);
+        }
+        dzy = FloatExp(0.0, 0);
         i = 0;
     } else {
         x = FixedDec(work[xidx + 0], bitcast<i32>(work[xidx + 1]), array<u32, LEN>());
@@ -53,38 +120,93 @@ fn main(
             x.parts[j] = work[xidx + 2 + j];
             y.parts[j] = work[yidx + 2 + j];
         }
+        dzx = FloatExp(bitcast<f32>(work[dzidx + 0]), bitcast<i32>(work[dzidx + 1]));
+        dzy = FloatExp(bitcast<f32>(work[dzidx + 2]), bitcast<i32>(work[dzidx + 3]));
     }
     let max = i + 1;
-    let thresh = from_f32(2.0 * 2.0);
+    let thresh = from_f32(BAILOUT * BAILOUT);
     loop {
-        // burning ship
-        // x.sign = 0;
-        // y.sign = 0;
         let x2 = mul(x, x);
         let y2 = mul(y, y);
-        if gt(add(x2, y2), thresh) || i >= max {
+        // generated by Formula::bailout and Formula::body
+        REPLACE_BAILOUT
+        if escaped || i >= max {
             break;
         }
-        let xy = mul(x, y);
-        y = add(add(xy, xy), cy);
-        x = add(sub(x2, y2), cx);
+        if DERIVATIVE {
+            // dz' = DEGREE * z^(DEGREE - 1) * dz + 1, the f32 z is plenty
+            let z = vec2(to_f32(x), to_f32(y));
+            var w = vec2(DEGREE, 0.0);
+            for (var k = 1u; k < u32(DEGREE); k += 1u) {
+                w = vec2(w.x * z.x - w.y * z.y, w.x * z.y + w.y * z.x);
+            }
+            let wx = fe_from_f32(w.x);
+            let wy = fe_from_f32(w.y);
+            let nx = fe_sub(fe_mul(wx, dzx), fe_mul(wy, dzy));
+            dzy = fe_add(fe_mul(wx, dzy), fe_mul(wy, dzx));
+            dzx = nx;
+            if !bool(view.julia) {
+                dzx = fe_add(dzx, FloatExp(0.5, 1));
+            }
+        }
+        REPLACE_FORMULA
         i += 1u;
     }
     work[worki] = i;
+    atomicMax(&progress.iters, i);
     work[xidx + 0] = x.sign; work[xidx + 1] = bitcast<u32>(x.dec);
     work[yidx + 0] = y.sign; work[yidx + 1] = bitcast<u32>(y.dec);
     for (var j = 0u; j < LEN; j += 1u) {
         work[xidx + 2 + j] = x.parts[j];
         work[yidx + 2 + j] = y.parts[j];
     }
-    var color = vec3<f32>(0.0, 0.0, 0.0);
+    work[dzidx + 0] = bitcast<u32>(dzx.mant); work[dzidx + 1] = bitcast<u32>(dzx.exp);
+    work[dzidx + 2] = bitcast<u32>(dzy.mant); work[dzidx + 3] = bitcast<u32>(dzy.exp);
+    let rawi = id.x * view.dims.y + id.y;
+    let prev = raw[rawi];
+    // x and y are the first z past the bailout if it escaped
+    let z = vec2(to_f32(x), to_f32(y));
+    let est = estimate(z, dzx, dzy);
+    var out = Raw(0u, 0u, i, f32(i), 0.0, est.x, est.yz);
     if i != max {
-        let pi = 3.1415;
-        let hue = f32(i) / 30.0;
-        color.r = cos(hue);
-        color.g = cos(hue - 2.0 * pi / 3.0);
-        color.b = cos(hue - 4.0 * pi / 3.0);
+        out.escaped = 1u;
+        out.abs_z = length(z);
+        out.normalized += 1.0 - log(log(out.abs_z)) / log(DEGREE);
+        if prev.escaped == 0u {
+            atomicAdd(&histogram[min(hist_bin(i), arrayLength(&histogram) - 1u)], 1u);
+        }
+    } else if length(z) > DONE_RADIUS && est.x >= 0.0 && est.x < DONE_DIST {
+        out.done = 1u;
+    }
+    if out.escaped == 0u && out.done == 0u {
+        atomicAdd(&progress.undecided, 1u);
+    }
+    raw[rawi] = out;
+}
+
+// the histogram bin for escaping at iteration i, see HIST_BITS in data.rs
+fn hist_bin(i: u32) -> u32 {
+    if i < (1u << HIST_BITS) {
+        return i;
+    }
+    let e = firstLeadingBit(i);
+    let mant = (i >> (e - HIST_BITS)) & ((1u << HIST_BITS) - 1u);
+    return ((e - HIST_BITS + 1u) << HIST_BITS) + mant;
+}
+
+// the exterior distance estimate |z| ln|z| / |dz| in pixels, then the normal;
+// only meaningful once |z| is past 1, and better the bigger it gets
+fn estimate(z: vec2<f32>, dzx: FloatExp, dzy: FloatExp) -> vec3<f32> {
+    let abs_z = length(z);
+    let abs_dz = fe_length(dzx, dzy);
+    if abs_z <= 1.0 || abs_dz.mant == 0.0 {
+        return vec3(-1.0, 0.0, 0.0);
     }
-    textureStore(output, id.xy, vec4(color, 1.0));
+    let pixel = fe_mul(view.fe_scale, fe_from_f32(view.stretch.x / f32(view.dims.x)));
+    let de = fe_to_f32(fe_div(fe_from_f32(abs_z * log(abs_z)), fe_mul(abs_dz, pixel)));
+    let d = vec2(fe_to_f32(fe_div(dzx, abs_dz)), fe_to_f32(fe_div(dzy, abs_dz)));
+    // z / dz, with dz scaled down to a unit first
+    let u = vec2(z.x * d.x + z.y * d.y, z.y * d.x - z.x * d.y);
+    return vec3(de, u / length(u));
 }
 
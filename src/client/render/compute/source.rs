@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use super::{ComputeMode, Formula};
+
+const FIXED_SHADER: &str = include_str!("fixed.wgsl");
+const FLOATEXP_SHADER: &str = include_str!("floatexp.wgsl");
+const SHADER: &str = include_str!("shader.wgsl");
+const PERTURB_SHADER: &str = include_str!("perturb.wgsl");
+
+// the wgsl the compute shader gets assembled from
+pub struct Sources {
+    fixed: String,
+    floatexp: String,
+    direct: String,
+    perturb: String,
+}
+
+impl Sources {
+    pub fn builtin() -> Self {
+        Self {
+            fixed: FIXED_SHADER.to_string(),
+            floatexp: FLOATEXP_SHADER.to_string(),
+            direct: SHADER.to_string(),
+            perturb: PERTURB_SHADER.to_string(),
+        }
+    }
+
+    // files in dir named like the builtin ones replace them
+    pub fn load(dir: &Path) -> Self {
+        let read = |name: &str, builtin: &str| {
+            std::fs::read_to_string(dir.join(name)).unwrap_or_else(|_| builtin.to_string())
+        };
+        Self {
+            fixed: read("fixed.wgsl", FIXED_SHADER),
+            floatexp: read("floatexp.wgsl", FLOATEXP_SHADER),
+            direct: read("shader.wgsl", SHADER),
+            perturb: read("perturb.wgsl", PERTURB_SHADER),
+        }
+    }
+
+    pub fn assemble(&self, len: usize, mode: ComputeMode, formula: &Formula) -> String {
+        let main = match mode {
+            ComputeMode::Direct => &self.direct,
+            ComputeMode::Perturbation => &self.perturb,
+        };
+        (self.fixed.clone() + &self.floatexp + main)
+            .replace("REPLACE_LEN", &format!("{}", len))
+            .replace("REPLACE_BAILOUT", &formula.bailout())
+            .replace("REPLACE_FORMULA", &formula.body())
+    }
+}
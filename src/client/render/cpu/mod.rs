@@ -0,0 +1,305 @@
+mod program;
+#[cfg(test)]
+mod test;
+
+use std::sync::Mutex;
+
+use nalgebra::Vector2;
+use program::{Program, Value, CX, CY, X, X2, Y, Y2};
+
+use crate::util::{
+    fe32::{self, Fe32},
+    wgsl, FixedDec, FloatExp,
+};
+
+use super::{
+    compute::{Raw, HIST_BITS, HIST_LEN},
+    headless::srgb,
+    output::{Coloring, Shading, CYCLE, PALETTE_LEN},
+    precision, Camera, Formula,
+};
+
+// the escape time loop of compute/shader.wgsl and the coloring pass on the
+// cpu, with the same fixed point rounding, so it gives the same iteration
+// counts and colors as render_headless. it's a lot slower, but it doesn't
+// need a gpu and it's what the gpu output gets checked against
+pub fn render_cpu(
+    camera: &Camera,
+    formula: &Formula,
+    iters: u32,
+    coloring: &Coloring,
+) -> Result<Vec<u8>, String> {
+    let len = precision(camera, None);
+    let program = Program::compile(formula, len)?;
+    let view = View::new(camera, len);
+    let degree = formula.degree() as f32;
+    let derivative = formula.differentiable();
+    let texels = coloring.palette.gradient.texels(PALETTE_LEN as usize);
+
+    let size = camera.size;
+    let mut raws = vec![Raw::default(); (size.x * size.y) as usize];
+    let rows = Mutex::new(raws.chunks_mut(size.x as usize).enumerate());
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    std::thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let Some((r, row)) = rows.lock().unwrap().next() else {
+                    break;
+                };
+                // rows go from the top of the screen down, the shader's y is
+                // from the bottom up
+                let y = size.y - 1 - r as u32;
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    let id = Vector2::new(x as u32, y);
+                    let (i, z, dz) = view.iterate(&program, id, iters, degree, derivative);
+                    *pixel = raw(i, z, view.estimate(z, dz), iters, degree);
+                }
+            });
+        }
+    });
+    let cdf = cdf(&raws);
+    Ok(raws
+        .iter()
+        .flat_map(|raw| color(raw, coloring, &texels, &cdf))
+        .collect())
+}
+
+struct View {
+    len: usize,
+    dims: Vector2<u32>,
+    stretch: Vector2<f32>,
+    fe_scale: Fe32,
+    scale: FixedDec,
+    corner_x: FixedDec,
+    corner_y: FixedDec,
+}
+
+impl View {
+    // the same values ComputeView::from_camera gives the shader
+    fn new(camera: &Camera, len: usize) -> Self {
+        let fit = |v: &FixedDec| v.clone().with_lens(1, len as i32 - 1);
+        // rounded to f32 like FloatExp::to_bytes
+        let fe_scale = FloatExp::from(camera.zoom.mult());
+        let mut scale = camera.zoom.mult().clone();
+        scale.set_precision(len);
+        Self {
+            len,
+            dims: camera.size,
+            stretch: camera.stretch(),
+            fe_scale: fe32::new(fe_scale.mant() as f32, fe_scale.exp()),
+            scale,
+            corner_x: fit(&camera.pos.x),
+            corner_y: fit(&camera.pos.y),
+        }
+    }
+
+    // how many iterations id took to escape, iters if it didn't, and the z
+    // it stopped at with dz/dc there, which stays 0 without derivative
+    fn iterate(
+        &self,
+        program: &Program,
+        id: Vector2<u32>,
+        iters: u32,
+        degree: f32,
+        derivative: bool,
+    ) -> (u32, [f32; 2], [Fe32; 2]) {
+        let fdims: Vector2<f32> = self.dims.cast();
+        let fpos = (id.cast::<f32>().component_div(&fdims) - Vector2::repeat(0.5))
+            .component_mul(&self.stretch);
+        let pos = |f: f32, corner: &FixedDec| {
+            wgsl::add(
+                &wgsl::mul(&wgsl::from_f32(f, self.len), &self.scale),
+                corner,
+            )
+        };
+        let mut slots = program.slots(self.len);
+        slots[CX] = Value::Fixed(pos(fpos.x, &self.corner_x));
+        slots[CY] = Value::Fixed(pos(fpos.y, &self.corner_y));
+        slots[X] = Value::Fixed(wgsl::zero(self.len));
+        slots[Y] = Value::Fixed(wgsl::zero(self.len));
+
+        let mut dz = [fe32::ZERO; 2];
+        let mut i = 0;
+        loop {
+            let (x, y) = (slots[X].fixed(), slots[Y].fixed());
+            let (x2, y2) = (wgsl::mul(x, x), wgsl::mul(y, y));
+            slots[X2] = Value::Fixed(x2);
+            slots[Y2] = Value::Fixed(y2);
+            if program.escaped(&mut slots) || i >= iters {
+                break;
+            }
+            if derivative {
+                let z = [
+                    wgsl::to_f32(slots[X].fixed()),
+                    wgsl::to_f32(slots[Y].fixed()),
+                ];
+                let mut w = [degree, 0.0];
+                for _ in 1..degree as u32 {
+                    w = [w[0] * z[0] - w[1] * z[1], w[0] * z[1] + w[1] * z[0]];
+                }
+                let (wx, wy) = (fe32::from_f32(w[0]), fe32::from_f32(w[1]));
+                let nx = fe32::sub(fe32::mul(wx, dz[0]), fe32::mul(wy, dz[1]));
+                let ny = fe32::add(fe32::mul(wx, dz[1]), fe32::mul(wy, dz[0]));
+                dz = [fe32::add(nx, fe32::ONE), ny];
+            }
+            program.step(&mut slots);
+            i += 1;
+        }
+        let z = [
+            wgsl::to_f32(slots[X].fixed()),
+            wgsl::to_f32(slots[Y].fixed()),
+        ];
+        (i, z, dz)
+    }
+
+    // estimate in shader.wgsl, the distance in pixels and the normal
+    fn estimate(&self, z: [f32; 2], dz: [Fe32; 2]) -> (f32, [f32; 2]) {
+        let abs_z = (z[0] * z[0] + z[1] * z[1]).sqrt();
+        let abs_dz = fe32::length(dz[0], dz[1]);
+        if abs_z <= 1.0 || abs_dz.mant == 0.0 {
+            return (-1.0, [0.0; 2]);
+        }
+        let pixel = fe32::mul(
+            self.fe_scale,
+            fe32::from_f32(self.stretch.x / self.dims.x as f32),
+        );
+        let de = fe32::to_f32(fe32::div(
+            fe32::from_f32(abs_z * abs_z.ln()),
+            fe32::mul(abs_dz, pixel),
+        ));
+        let d = dz.map(|d| fe32::to_f32(fe32::div(d, abs_dz)));
+        let u = [z[0] * d[0] + z[1] * d[1], z[1] * d[0] - z[0] * d[1]];
+        let len = (u[0] * u[0] + u[1] * u[1]).sqrt();
+        (de, u.map(|u| u / len))
+    }
+}
+
+// what the shader leaves in the raw buffer after iterating. degree is the
+// formula's power; done is left out since nothing here goes by it
+fn raw(i: u32, z: [f32; 2], (de, normal): (f32, [f32; 2]), iters: u32, degree: f32) -> Raw {
+    let mut raw = Raw {
+        iters: i,
+        normalized: i as f32,
+        de,
+        normal,
+        ..Default::default()
+    };
+    if i != iters {
+        raw.escaped = 1;
+        raw.abs_z = (z[0] * z[0] + z[1] * z[1]).sqrt();
+        raw.normalized += 1.0 - raw.abs_z.ln().ln() / degree.ln();
+    }
+    raw
+}
+
+// the histogram the compute shader counts escapes in, summed up the way the
+// scan in color.wgsl does
+fn cdf(raws: &[Raw]) -> Vec<u32> {
+    let mut histogram = vec![0; HIST_LEN];
+    for raw in raws.iter().filter(|r| r.escaped != 0) {
+        histogram[(hist_bin(raw.iters) as usize).min(HIST_LEN - 1)] += 1;
+    }
+    histogram
+        .iter()
+        .scan(0, |total, n| {
+            *total += n;
+            Some(*total)
+        })
+        .collect()
+}
+
+// hist_bin in shader.wgsl
+fn hist_bin(i: u32) -> u32 {
+    if i < 1 << HIST_BITS {
+        return i;
+    }
+    let e = 31 - i.leading_zeros();
+    let mant = (i >> (e - HIST_BITS)) & ((1 << HIST_BITS) - 1);
+    ((e - HIST_BITS + 1) << HIST_BITS) + mant
+}
+
+// bin_range in color.wgsl
+fn bin_range(bin: u32) -> (u32, u32) {
+    if bin < 1 << HIST_BITS {
+        return (bin, 1);
+    }
+    let sh = (bin >> HIST_BITS) - 1;
+    let mant = bin & ((1 << HIST_BITS) - 1);
+    (((1 << HIST_BITS) + mant) << sh, 1 << sh)
+}
+
+// equalized in color.wgsl
+fn equalized(n: f32, cdf: &[u32]) -> f32 {
+    let total = cdf[cdf.len() - 1];
+    if total == 0 {
+        return 0.0;
+    }
+    let bin = (hist_bin(n.max(0.0) as u32) as usize).min(cdf.len() - 1);
+    let before = if bin > 0 { cdf[bin - 1] } else { 0 };
+    let (start, width) = bin_range(bin as u32);
+    let f = ((n - start as f32) / width as f32).clamp(0.0, 1.0);
+    (before as f32 + f * (cdf[bin] - before) as f32) / total as f32
+}
+
+// the constants in color.wgsl
+const LINE_WIDTH: f32 = 1.0;
+const DIST_CYCLE: f32 = 16.0;
+const MIN_DIST: f32 = 1e-6;
+const LIGHT: [f32; 2] = [std::f32::consts::FRAC_1_SQRT_2; 2];
+const RELIEF: f32 = 4.0;
+const HEIGHT: f32 = 1.5;
+
+// shade in color.wgsl
+fn shade(raw: &Raw, shading: Shading) -> f32 {
+    let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+    match shading {
+        Shading::Lines => (raw.de / LINE_WIDTH).min(1.0),
+        Shading::Lambert => {
+            let n = [raw.normal[0], raw.normal[1], raw.de.min(1e6) / RELIEF];
+            let light = [
+                LIGHT[0] * std::f32::consts::FRAC_1_SQRT_2,
+                LIGHT[1] * std::f32::consts::FRAC_1_SQRT_2,
+                std::f32::consts::FRAC_1_SQRT_2,
+            ];
+            // inverseSqrt, which llvmpipe gets to the same bits as this
+            (dot(&n, &light) * (1.0 / dot(&n, &n).sqrt())).max(0.0)
+        }
+        Shading::NormalMap => ((dot(&raw.normal, &LIGHT) + HEIGHT) / (1.0 + HEIGHT)).max(0.0),
+        Shading::None | Shading::Distance => 1.0,
+    }
+}
+
+// the coloring pass in output/color.wgsl, written to an rgba8unorm texture
+// and then read back the way render_headless does. texels are what goes in
+// the palette texture
+fn color(raw: &Raw, coloring: &Coloring, texels: &[[f32; 4]], cdf: &[u32]) -> [u8; 4] {
+    let mut color = [0.0; 3];
+    if raw.escaped != 0 {
+        let n = if coloring.smooth {
+            raw.normalized
+        } else {
+            raw.iters as f32
+        };
+        let has_de = raw.de >= 0.0;
+        let x = if has_de && coloring.shading == Shading::Distance {
+            raw.de.max(MIN_DIST).log2() * coloring.density / DIST_CYCLE + coloring.offset
+        } else if coloring.equalize {
+            equalized(n, cdf) * coloring.density + coloring.offset
+        } else {
+            n * coloring.density / CYCLE + coloring.offset
+        };
+        // palette_color in color.wgsl
+        let t = (x - x.floor()) * texels.len() as f32;
+        let i = t as usize;
+        let f = t - i as f32;
+        let a = texels[i % texels.len()];
+        let b = texels[(i + 1) % texels.len()];
+        color = std::array::from_fn(|c| a[c] + (b[c] - a[c]) * f);
+        if has_de {
+            let shade = shade(raw, coloring.shading);
+            color = color.map(|c| c * shade);
+        }
+    }
+    let unorm = |c: f32| srgb((c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    [unorm(color[0]), unorm(color[1]), unorm(color[2]), 255]
+}
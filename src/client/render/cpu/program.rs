@@ -0,0 +1,326 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::util::{wgsl, FixedDec};
+
+use super::super::Formula;
+
+// runs a formula's bailout and body by interpreting the same wgsl that goes
+// in the shader, so scripts don't need a second backend. the generated code
+// only uses lets, vars, assignments, sign changes, and the fixed point
+// functions, which is all this understands
+pub struct Program {
+    bailout: Vec<Stmt>,
+    body: Vec<Stmt>,
+    slots: usize,
+    escaped: usize,
+}
+
+// the slots in scope in the shader loop before the formula runs
+pub const X: usize = 0;
+pub const Y: usize = 1;
+pub const X2: usize = 2;
+pub const Y2: usize = 3;
+pub const CX: usize = 4;
+pub const CY: usize = 5;
+const THRESH: usize = 6;
+const NAMES: [&str; 7] = ["x", "y", "x2", "y2", "cx", "cy", "thresh"];
+// BAILOUT in shader.wgsl
+const BAILOUT: f32 = 128.0;
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Fixed(FixedDec),
+    // signs are true when negative, same as FixedDec
+    Bool(bool),
+}
+
+#[derive(Debug)]
+enum Expr {
+    Slot(usize),
+    Const(Value),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Sqrt(Box<Expr>),
+    Pow(Box<Expr>, u32),
+    Gt(Box<Expr>, Box<Expr>),
+    Sign(Box<Expr>),
+    Eq(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Stmt {
+    Set(usize, Expr),
+    SetSign(usize, Expr),
+}
+
+impl Program {
+    pub fn compile(formula: &Formula, len: usize) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: Vec::new(),
+            i: 0,
+            names: NAMES
+                .iter()
+                .enumerate()
+                .map(|(i, n)| (n.to_string(), i))
+                .collect(),
+            len,
+        };
+        let err = |e| format!("the cpu renderer can't run {}: {}", formula, e);
+        let bailout = parser.stmts(&formula.bailout()).map_err(err)?;
+        let escaped = parser.slot("escaped").map_err(err)?;
+        let body = parser.stmts(&formula.body()).map_err(err)?;
+        Ok(Self {
+            bailout,
+            body,
+            slots: parser.names.len(),
+            escaped,
+        })
+    }
+
+    // a slot for every name, with thresh filled in like the shader does
+    pub fn slots(&self, len: usize) -> Vec<Value> {
+        let mut slots = vec![Value::Bool(false); self.slots];
+        slots[THRESH] = Value::Fixed(wgsl::from_f32(BAILOUT * BAILOUT, len));
+        slots
+    }
+
+    pub fn escaped(&self, slots: &mut [Value]) -> bool {
+        run(&self.bailout, slots);
+        slots[self.escaped].bool()
+    }
+
+    pub fn step(&self, slots: &mut [Value]) {
+        run(&self.body, slots);
+    }
+}
+
+fn run(stmts: &[Stmt], slots: &mut [Value]) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Set(slot, expr) => slots[*slot] = expr.eval(slots),
+            Stmt::SetSign(slot, expr) => {
+                let neg = expr.eval(slots).bool();
+                if let Value::Fixed(x) = &mut slots[*slot] {
+                    wgsl::set_sign(x, neg);
+                }
+            }
+        }
+    }
+}
+
+impl Value {
+    pub fn fixed(&self) -> &FixedDec {
+        match self {
+            Self::Fixed(x) => x,
+            Self::Bool(_) => panic!("expected a fixed point value"),
+        }
+    }
+
+    fn bool(&self) -> bool {
+        match self {
+            Self::Bool(b) => *b,
+            Self::Fixed(_) => panic!("expected a bool"),
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, slots: &[Value]) -> Value {
+        let fixed = |e: &Expr| e.fixed(slots);
+        match self {
+            Self::Slot(slot) => slots[*slot].clone(),
+            Self::Const(v) => v.clone(),
+            Self::Add(a, b) => Value::Fixed(wgsl::add(&fixed(a), &fixed(b))),
+            Self::Sub(a, b) => Value::Fixed(wgsl::sub(&fixed(a), &fixed(b))),
+            Self::Mul(a, b) => Value::Fixed(wgsl::mul(&fixed(a), &fixed(b))),
+            Self::Sqrt(a) => Value::Fixed(wgsl::sqrt(&fixed(a))),
+            Self::Pow(a, n) => Value::Fixed(wgsl::pow(&fixed(a), *n)),
+            Self::Gt(a, b) => Value::Bool(wgsl::gt(&fixed(a), &fixed(b))),
+            Self::Sign(a) => Value::Bool(fixed(a).is_neg()),
+            Self::Eq(a, b) => Value::Bool(a.eval(slots).bool() == b.eval(slots).bool()),
+        }
+    }
+
+    // borrows slots instead of cloning them
+    fn fixed<'a>(&self, slots: &'a [Value]) -> Cow<'a, FixedDec> {
+        match self {
+            Self::Slot(slot) => Cow::Borrowed(slots[*slot].fixed()),
+            e => match e.eval(slots) {
+                Value::Fixed(x) => Cow::Owned(x),
+                Value::Bool(_) => panic!("expected a fixed point value"),
+            },
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut token = c.to_string();
+        let number = c.is_ascii_digit();
+        if number || c.is_alphabetic() || c == '_' {
+            while let Some(&c) = chars.peek() {
+                let exp_sign = number && c == '-' && token.ends_with('e');
+                let point = number && c == '.';
+                if !(c.is_alphanumeric() || c == '_' || point || exp_sign) {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+        } else if c == '=' && chars.peek() == Some(&'=') {
+            token.push('=');
+            chars.next();
+        }
+        tokens.push(token);
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    i: usize,
+    names: HashMap<String, usize>,
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &str {
+        self.tokens.get(self.i).map_or("", |t| t.as_str())
+    }
+
+    fn next(&mut self) -> String {
+        let token = self.peek().to_string();
+        self.i += 1;
+        token
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        let found = self.next();
+        if found != token {
+            return Err(format!("expected '{}', found '{}'", token, found));
+        }
+        Ok(())
+    }
+
+    fn slot(&self, name: &str) -> Result<usize, String> {
+        self.names
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("unknown name '{}'", name))
+    }
+
+    fn stmts(&mut self, source: &str) -> Result<Vec<Stmt>, String> {
+        self.tokens = tokenize(source);
+        self.i = 0;
+        let mut stmts = Vec::new();
+        while self.i < self.tokens.len() {
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, String> {
+        let first = self.next();
+        let stmt = if first == "let" || first == "var" {
+            let name = self.next();
+            self.expect("=")?;
+            let expr = self.expr()?;
+            let slot = self.names.len();
+            if self.names.insert(name.clone(), slot).is_some() {
+                return Err(format!("'{}' is declared twice", name));
+            }
+            Stmt::Set(slot, expr)
+        } else {
+            let slot = self.slot(&first)?;
+            if self.peek() == "." {
+                self.next();
+                self.expect("sign")?;
+                self.expect("=")?;
+                Stmt::SetSign(slot, self.expr()?)
+            } else {
+                self.expect("=")?;
+                Stmt::Set(slot, self.expr()?)
+            }
+        };
+        self.expect(";")?;
+        Ok(stmt)
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let lhs = self.atom()?;
+        if self.peek() != "==" {
+            return Ok(lhs);
+        }
+        self.next();
+        Ok(Expr::Eq(Box::new(lhs), Box::new(self.atom()?)))
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let name = self.next();
+        let expr = match name.as_str() {
+            "POS" => return Ok(Expr::Const(Value::Bool(false))),
+            "NEG" => return Ok(Expr::Const(Value::Bool(true))),
+            _ if self.peek() == "(" => self.call(&name)?,
+            _ => Expr::Slot(self.slot(&name)?),
+        };
+        if self.peek() != "." {
+            return Ok(expr);
+        }
+        self.next();
+        self.expect("sign")?;
+        Ok(Expr::Sign(Box::new(expr)))
+    }
+
+    fn call(&mut self, name: &str) -> Result<Expr, String> {
+        self.expect("(")?;
+        let expr = match name {
+            "zero" => Expr::Const(Value::Fixed(wgsl::zero(self.len))),
+            "one" => Expr::Const(Value::Fixed(wgsl::one(self.len))),
+            "from_f32" => {
+                let neg = self.peek() == "-";
+                if neg {
+                    self.next();
+                }
+                let num = self.next();
+                let value: f32 = num
+                    .parse()
+                    .map_err(|_| format!("expected a number, found '{}'", num))?;
+                let value = if neg { -value } else { value };
+                Expr::Const(Value::Fixed(wgsl::from_f32(value, self.len)))
+            }
+            // signs are already bools here
+            "u32" => self.expr()?,
+            "sqrt" => Expr::Sqrt(Box::new(self.expr()?)),
+            "pow" => {
+                let a = Box::new(self.expr()?);
+                self.expect(",")?;
+                let num = self.next();
+                let n = num
+                    .trim_end_matches('u')
+                    .parse()
+                    .map_err(|_| format!("expected a power, found '{}'", num))?;
+                Expr::Pow(a, n)
+            }
+            "add" | "sub" | "mul" | "gt" => {
+                let a = Box::new(self.expr()?);
+                self.expect(",")?;
+                let b = Box::new(self.expr()?);
+                match name {
+                    "add" => Expr::Add(a, b),
+                    "sub" => Expr::Sub(a, b),
+                    "mul" => Expr::Mul(a, b),
+                    _ => Expr::Gt(a, b),
+                }
+            }
+            _ => return Err(format!("unknown function '{}'", name)),
+        };
+        self.expect(")")?;
+        Ok(expr)
+    }
+}
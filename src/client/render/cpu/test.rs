@@ -0,0 +1,168 @@
+use std::path::Path;
+
+use nalgebra::Vector2;
+
+use crate::util::{FixedDec, Script};
+
+use super::{
+    super::{
+        compute::{Raw, HIST_LEN},
+        Camera, Coloring, Formula, Shading,
+    },
+    bin_range, cdf, equalized, hist_bin,
+    program::Program,
+    render_cpu, shade, View,
+};
+
+// a camera with c exactly at pixel (1, 1)
+fn camera_at(x: f64, y: f64) -> Camera {
+    let mut camera = Camera::julia(Vector2::new(2, 2));
+    camera.pos = Vector2::new(x, y).map(FixedDec::from);
+    camera
+}
+
+fn iterate(x: f64, y: f64, iters: u32) -> (u32, [f32; 2]) {
+    let camera = camera_at(x, y);
+    let program = Program::compile(&Formula::Mandelbrot, 3).unwrap();
+    let view = View::new(&camera, 3);
+    let (i, z, _) = view.iterate(&program, Vector2::new(1, 1), iters, 2.0, true);
+    (i, z)
+}
+
+#[test]
+fn known_points() {
+    assert_eq!(iterate(0.0, 0.0, 100), (100, [0.0, 0.0]));
+    // on the boundary, but still bounded
+    assert_eq!(iterate(-2.0, 0.0, 100).0, 100);
+    assert_eq!(iterate(0.25, 0.0, 100).0, 100);
+    // 0, 1, 2, 5, 26, 677, which is past 128
+    assert_eq!(iterate(1.0, 0.0, 100), (5, [677.0, 0.0]));
+    assert_eq!(iterate(1.0, 0.0, 3).0, 3);
+}
+
+#[test]
+fn known_colors() {
+    // row 0 is the top of the screen, which is pixel y = 1
+    let pixel = |x, y| {
+        let image = render_cpu(
+            &camera_at(x, y),
+            &Formula::Mandelbrot,
+            100,
+            &Coloring::default(),
+        );
+        image.unwrap()[4..8].to_vec()
+    };
+    assert_eq!(pixel(0.0, 0.0), [0, 0, 0, 255]);
+    assert_ne!(pixel(1.0, 0.0), [0, 0, 0, 255]);
+}
+
+#[test]
+fn compiles_formulas() {
+    for formula in Formula::builtin() {
+        assert!(Program::compile(&formula, 3).is_ok(), "{}", formula);
+    }
+    let count = std::fs::read_dir("formulas").unwrap().count();
+    let scripts = Formula::load_dir(Path::new("formulas"));
+    assert_eq!(scripts.len(), count);
+    for formula in scripts {
+        assert!(Program::compile(&formula, 3).is_ok(), "{}", formula);
+    }
+}
+
+#[test]
+fn unknown_wgsl() {
+    let script = |bailout: &str, body: &str| Formula::Script {
+        name: "test".to_string(),
+        script: Script {
+            bailout: bailout.to_string(),
+            body: body.to_string(),
+        },
+    };
+    let bailout = "let escaped = gt(add(x2, y2), thresh);";
+    let body = "y = add(mul(x, y), cy); x = add(sub(x2, y2), cx);";
+    assert!(Program::compile(&script(bailout, body), 3).is_ok());
+    let unknown = [
+        script("let escaped = length(x) > 2.0;", body),
+        script(bailout, "y = sin(x);"),
+        script(bailout, "y = add(w, cy);"),
+        script(bailout, "for (var i = 0; i < 2; i++) {}"),
+        script("let done = gt(x2, thresh);", body),
+    ];
+    for formula in unknown {
+        let err = Program::compile(&formula, 3).err().unwrap();
+        assert!(
+            err.starts_with("the cpu renderer can't run test: "),
+            "{}",
+            err
+        );
+    }
+}
+
+fn escaped(iters: u32) -> Raw {
+    Raw {
+        escaped: 1,
+        iters,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn histogram() {
+    let raws = [escaped(2), escaped(5), Raw::default(), escaped(2)];
+    let cdf = cdf(&raws);
+    assert_eq!(cdf[..7], [0, 0, 2, 2, 2, 3, 3]);
+    assert_eq!(cdf[cdf.len() - 1], 3);
+    assert_eq!(equalized(2.0, &cdf), 0.0);
+    assert_eq!(equalized(2.5, &cdf), 1.0 / 3.0);
+    assert_eq!(equalized(5.0, &cdf), 2.0 / 3.0);
+    assert_eq!(equalized(6.0, &cdf), 1.0);
+    assert_eq!(equalized(-1.0, &cdf), 0.0);
+    // nothing escaped
+    assert_eq!(equalized(3.0, &super::cdf(&[Raw::default()])), 0.0);
+}
+
+#[test]
+fn histogram_bins() {
+    let contains = |i: u32| {
+        let (start, width) = bin_range(hist_bin(i));
+        start <= i && i - start < width
+    };
+    for i in 1..1 << 20 {
+        assert!(contains(i), "{}", i);
+        let step = hist_bin(i) - hist_bin(i - 1);
+        assert!(step <= 1, "{} is {} bins past {}", i, step, i - 1);
+    }
+    assert!(contains(u32::MAX - 1) && contains(u32::MAX));
+    assert_eq!(hist_bin(u32::MAX) as usize, HIST_LEN - 1);
+    assert_eq!(hist_bin(16383), 16383);
+    // deep zooms where everything escapes past a million still get spread
+    // out, 32 iterations to a bin there
+    let raws = [escaped(1_000_000), escaped(1_000_096), escaped(1_000_192)];
+    let cdf = cdf(&raws);
+    assert_eq!(equalized(1_000_000.0, &cdf), 0.0);
+    assert_eq!(equalized(1_000_096.0, &cdf), 1.0 / 3.0);
+    assert_eq!(equalized(1_000_192.0, &cdf), 2.0 / 3.0);
+}
+
+#[test]
+fn shading() {
+    let raw = |de, normal| Raw {
+        escaped: 1,
+        de,
+        normal,
+        ..Default::default()
+    };
+    let light = [std::f32::consts::FRAC_1_SQRT_2; 2];
+    assert_eq!(shade(&raw(0.5, light), Shading::Lines), 0.5);
+    assert_eq!(shade(&raw(3.0, light), Shading::Lines), 1.0);
+    assert_eq!(shade(&raw(0.5, light), Shading::None), 1.0);
+    assert_eq!(shade(&raw(0.5, light), Shading::Distance), 1.0);
+    let facing = shade(&raw(0.5, light), Shading::NormalMap);
+    assert!((facing - 1.0).abs() < 1e-6);
+    let away = shade(&raw(0.5, light.map(|l| -l)), Shading::NormalMap);
+    assert!((away - 0.2).abs() < 1e-6);
+    // flat, so only the light's height counts
+    let flat = shade(&raw(1e9, [0.0; 2]), Shading::Lambert);
+    assert!((flat - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    assert_eq!(shade(&raw(0.0, light.map(|l| -l)), Shading::Lambert), 0.0);
+}
@@ -0,0 +1,169 @@
+use nalgebra::Vector2;
+
+use super::{
+    compute::{ComputeMode, ComputePipeline, Formula},
+    output::{ColorPipeline, Coloring},
+    precision, Camera,
+};
+
+// dispatches recorded per submit; the direct shader does one iteration each
+const BATCH: u32 = 64;
+
+// runs the compute shader for iters iterations without a window, colors it
+// and reads back the output, as rgba rows from the top of the screen down. software
+// falls back to a cpu adapter if there isn't a gpu either way
+pub fn render_headless(
+    camera: &Camera,
+    formula: &Formula,
+    iters: u32,
+    coloring: &Coloring,
+    software: bool,
+) -> Result<Vec<u8>, String> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+    let request = |force_fallback_adapter| {
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter,
+        }))
+    };
+    let adapter = request(software)
+        .or_else(|| request(true))
+        .ok_or("Could not get adapter! --cpu renders without one")?;
+    let info = adapter.get_info();
+    println!("Adapter: {} ({:?})", info.name, info.device_type);
+    println!("Backend: {:?}", info.backend);
+
+    let size = camera.size;
+    let len = precision(camera, None);
+    let limits = adapter.limits();
+    let work_bytes = ComputePipeline::work_bytes(size, len);
+    if work_bytes > limits.max_storage_buffer_binding_size as u64 {
+        return Err(format!(
+            "{}x{} needs {} MB of work buffer but the adapter allows {} MB, try a smaller size",
+            size.x,
+            size.y,
+            work_bytes >> 20,
+            limits.max_storage_buffer_binding_size >> 20,
+        ));
+    }
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: None,
+            required_features: wgpu::Features::empty(),
+            required_limits: limits,
+            memory_hints: wgpu::MemoryHints::default(),
+        },
+        None,
+    ))
+    .map_err(|e| format!("Could not get device: {}", e))?;
+
+    let mut belt = wgpu::util::StagingBelt::new(1024);
+    let mut pipeline = ComputePipeline::init(&device, size, len);
+    let mut done = 0;
+    while done < iters {
+        let batch = BATCH.min(iters - done);
+        let mut encoder = device.create_command_encoder(&Default::default());
+        for _ in 0..batch {
+            pipeline.update(
+                &device,
+                &mut encoder,
+                &mut belt,
+                camera,
+                len,
+                ComputeMode::Direct,
+                formula,
+                None,
+            );
+            pipeline.run(&mut encoder);
+        }
+        belt.finish();
+        queue.submit(Some(encoder.finish()));
+        belt.recall();
+        device.poll(wgpu::Maintain::Wait);
+        done += batch;
+    }
+
+    let mut color = ColorPipeline::init(&device, size, &pipeline.raw, &pipeline.histogram);
+    let mut encoder = device.create_command_encoder(&Default::default());
+    color.update(&device, &mut encoder, &mut belt, coloring);
+    color.run(&mut encoder);
+    belt.finish();
+    queue.submit(Some(encoder.finish()));
+    belt.recall();
+
+    Ok(read_output(&device, &queue, &color.output.texture, size))
+}
+
+pub(super) fn read_output(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    size: Vector2<u32>,
+) -> Vec<u8> {
+    let row = size.x * 4;
+    let padded_row = row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("output readback"),
+        size: (padded_row * size.y) as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&Default::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |res| {
+        res.expect("Could not read output!")
+    });
+    device.poll(wgpu::Maintain::Wait);
+    let data = slice.get_mapped_range();
+    // the texture is upside down and linear, the output pass flips it and
+    // the srgb surface encodes it. alpha only tells the output pass which
+    // pixels are unfinished
+    data.chunks(padded_row as usize)
+        .rev()
+        .flat_map(|r| &r[..row as usize])
+        .enumerate()
+        .map(|(i, &b)| if i % 4 == 3 { 255 } else { srgb(b) })
+        .collect()
+}
+
+pub(super) fn srgb(linear: u8) -> u8 {
+    let c = linear as f32 / 255.0;
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+pub(super) fn linear(srgb: u8) -> u8 {
+    let c = srgb as f32 / 255.0;
+    let c = if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    };
+    (c * 255.0).round() as u8
+}
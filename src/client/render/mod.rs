@@ -1,17 +1,28 @@
 mod compute;
+mod cpu;
+mod headless;
 mod output;
+mod saved;
 mod util;
 mod view;
 
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
-use compute::ComputePipeline;
+pub use compute::{ComputeMode, Formula, Progress, WorkState};
+use compute::{ComputePipeline, Sources};
+pub use cpu::*;
+pub use headless::*;
 use nalgebra::Vector2;
-use output::RenderPipeline;
+use output::{ColorPipeline, RenderPipeline, COLOR_SHADER};
+pub use output::{Coloring, Palette, Shading};
+use saved::SavedSnapshots;
 use util::GPUTimer;
 use view::ChunkView;
+pub use view::SnapshotKind;
 use winit::{dpi::PhysicalSize, window::Window};
 
+use crate::util::FixedDec;
+
 use super::camera::Camera;
 
 const CHUNK_POW: u32 = 7;
@@ -27,9 +38,11 @@ pub struct Renderer<'a> {
     staging_belt: wgpu::util::StagingBelt,
     timer: GPUTimer,
     chunk_view: ChunkView,
+    saved: SavedSnapshots,
     len: usize,
 
     compute_pipeline: ComputePipeline,
+    color_pipeline: ColorPipeline,
     render_pipeline: RenderPipeline,
 }
 
@@ -102,12 +115,20 @@ impl Renderer<'_> {
 
         let len = 2;
 
-        let compute_pipeline = ComputePipeline::init(&device, &config, len);
-        let render_pipeline = RenderPipeline::init(&device, &config, &compute_pipeline.output);
+        let compute_pipeline =
+            ComputePipeline::init(&device, Vector2::new(config.width, config.height), len);
+        let color_pipeline = ColorPipeline::init(
+            &device,
+            Vector2::new(config.width, config.height),
+            &compute_pipeline.raw,
+            &compute_pipeline.histogram,
+        );
+        let render_pipeline = RenderPipeline::init(&device, &config, &color_pipeline.output);
 
         Self {
             render_pipeline,
             compute_pipeline,
+            color_pipeline,
             size: Vector2::new(size.width, size.height),
             staging_belt,
             surface,
@@ -117,13 +138,21 @@ impl Renderer<'_> {
             config,
             queue,
             chunk_view: ChunkView::new(),
+            saved: SavedSnapshots::load(),
             len,
         }
     }
 
-    pub fn render(&mut self, camera: &Camera, snapshot: bool) {
-        // at level 0 I want 3, and should increase respective to bits needed for positioning
-        self.len = (camera.zoom.level() / 32) as usize + 3;
+    pub fn render(
+        &mut self,
+        camera: &Camera,
+        snapshot: Option<SnapshotKind>,
+        mode: ComputeMode,
+        formula: &Formula,
+        julia: Option<&Vector2<FixedDec>>,
+        coloring: &Coloring,
+    ) {
+        self.len = precision(camera, julia);
 
         self.compute_pipeline.update(
             &self.device,
@@ -131,15 +160,36 @@ impl Renderer<'_> {
             &mut self.staging_belt,
             camera,
             self.len,
+            mode,
+            formula,
+            julia,
+        );
+        self.color_pipeline.update(
+            &self.device,
+            &mut self.encoder,
+            &mut self.staging_belt,
+            coloring,
         );
-        self.chunk_view.update(camera, snapshot);
+        let capacity = output::snapshot_capacity(self.size);
+        let layer = snapshot.map(|kind| {
+            let saved = (kind == SnapshotKind::Manual)
+                .then(|| self.save_snapshot(camera, formula, julia))
+                .flatten();
+            self.chunk_view
+                .push(camera, camera.clone(), saved, self.size, capacity as usize)
+        });
+        if layer.is_none() {
+            self.load_snapshot(camera, formula, julia, capacity);
+        }
+        self.chunk_view.update(camera);
         self.render_pipeline.update(
             &self.device,
             &mut self.encoder,
             &mut self.staging_belt,
             &self.chunk_view.render,
-            &self.compute_pipeline.output,
-            snapshot,
+            &self.color_pipeline.output,
+            layer,
+            capacity,
         );
 
         let mut encoder = std::mem::replace(&mut self.encoder, Self::create_encoder(&self.device));
@@ -148,27 +198,147 @@ impl Renderer<'_> {
         self.timer.start(&mut encoder, 0);
         self.compute_pipeline.run(&mut encoder);
         self.timer.stop(&mut encoder, 0);
+        self.color_pipeline.run(&mut encoder);
+        self.compute_pipeline.progress.copy(&mut encoder);
         self.timer.resolve(&mut encoder);
 
         self.render_pipeline.draw(&mut encoder, &output);
 
         self.staging_belt.finish();
         self.queue.submit(std::iter::once(encoder.finish()));
+        self.compute_pipeline.progress.map();
         output.present();
         self.staging_belt.recall();
 
         self.timer.finish(&self.device);
     }
 
+    // the snapshot is of the last frame's output, which hasn't been
+    // replaced yet
+    fn save_snapshot(
+        &mut self,
+        camera: &Camera,
+        formula: &Formula,
+        julia: Option<&Vector2<FixedDec>>,
+    ) -> Option<usize> {
+        let data = headless::read_output(
+            &self.device,
+            &self.queue,
+            &self.color_pipeline.output.texture,
+            self.size,
+        );
+        self.saved
+            .save(&data, camera, formula, julia)
+            .map_err(|e| println!("could not save snapshot: {}", e))
+            .ok()
+    }
+
+    // brings back the closest saved snapshot that's on screen, if it
+    // doesn't push out one that's closer. the png is read on another thread,
+    // so it shows up a few frames after it's asked for
+    fn load_snapshot(
+        &mut self,
+        camera: &Camera,
+        formula: &Formula,
+        julia: Option<&Vector2<FixedDec>>,
+        capacity: u32,
+    ) {
+        let chunk_view = &self.chunk_view;
+        let Some((i, levels)) = self
+            .saved
+            .nearest(camera, formula, julia, |i| chunk_view.has_saved(i))
+        else {
+            return;
+        };
+        if !chunk_view.has_room(camera, self.size, levels, capacity as usize) {
+            return;
+        }
+        self.saved.request(i, self.size);
+        let Some((i, data)) = self.saved.poll(self.size) else {
+            return;
+        };
+        // the view could have moved on while it was read, so it has to still
+        // be on screen itself, with everything but it counted as loaded
+        let still_near = self.saved.nearest(camera, formula, julia, |j| {
+            j != i || chunk_view.has_saved(j)
+        });
+        let Some((_, levels)) = still_near else {
+            return;
+        };
+        if !chunk_view.has_room(camera, self.size, levels, capacity as usize) {
+            return;
+        }
+        let Some(ss_cam) = self.saved.camera(i).cloned() else {
+            return;
+        };
+        let layer = self
+            .chunk_view
+            .push(camera, ss_cam, Some(i), self.size, capacity as usize);
+        self.render_pipeline.write_snapshot(
+            &self.device,
+            &self.queue,
+            &self.color_pipeline.output,
+            layer,
+            capacity,
+            &data,
+        );
+    }
+
+    // everything iterated so far, for a checkpoint
+    pub fn save_work(&self) -> WorkState {
+        self.compute_pipeline.save_work(&self.device, &self.queue)
+    }
+
+    pub fn restore_work(
+        &mut self,
+        camera: &Camera,
+        formula: &Formula,
+        julia: Option<&Vector2<FixedDec>>,
+        state: WorkState,
+    ) -> Result<(), String> {
+        if precision(camera, julia) != state.len {
+            return Err(format!("it's for precision {}", state.len));
+        }
+        self.len = state.len;
+        self.compute_pipeline
+            .restore(&self.device, camera, formula, julia, state)
+    }
+
+    // how far the compute shader had gotten a frame or so ago, if it's been
+    // read back since the last call
+    pub fn progress(&mut self) -> Option<Progress> {
+        self.compute_pipeline.progress.read()
+    }
+
+    // reloads the compute and coloring shaders from the wgsl files in dir,
+    // keeping the builtin version of any that aren't there
+    pub fn reload_shaders(&mut self, dir: &Path) {
+        self.compute_pipeline.set_sources(Sources::load(dir));
+        let color = std::fs::read_to_string(dir.join("color.wgsl"))
+            .unwrap_or_else(|_| COLOR_SHADER.to_string());
+        self.color_pipeline.set_source(&self.device, &color);
+    }
+
     pub fn resize(&mut self, size: PhysicalSize<u32>) {
-        self.size = Vector2::new(size.width, size.height);
-        self.config.width = size.width;
-        self.config.height = size.height;
+        let size = Vector2::new(size.width, size.height);
+        // the first resize is often to the size it already is, and
+        // shouldn't throw away a restored work buffer
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        self.config.width = size.x;
+        self.config.height = size.y;
         self.surface.configure(&self.device, &self.config);
-        self.compute_pipeline
-            .resize(&self.device, self.size, self.len);
+        self.compute_pipeline.resize(&self.device, self.size);
+        self.color_pipeline.resize(
+            &self.device,
+            self.size,
+            &self.compute_pipeline.raw,
+            &self.compute_pipeline.histogram,
+        );
         self.render_pipeline
-            .resize(&self.device, &self.compute_pipeline.output);
+            .resize(&self.device, &self.color_pipeline.output);
     }
 
     fn create_encoder(device: &wgpu::Device) -> wgpu::CommandEncoder {
@@ -181,3 +351,18 @@ impl Renderer<'_> {
         &self.size
     }
 }
+
+pub fn precision(camera: &Camera, julia: Option<&Vector2<FixedDec>>) -> usize {
+    // at level 0 I want 3, and should increase respective to bits needed for positioning
+    let len = (camera.zoom.level() / 32) as usize + 3;
+    // a julia c picked deep in the mandelbrot view needs all of its parts even
+    // though the julia camera is zoomed out
+    julia.map_or(len, |c| len.max(fit_len(&c.x)).max(fit_len(&c.y)))
+}
+
+// parts for a value with the one whole part the shaders use
+fn fit_len(v: &FixedDec) -> usize {
+    let mut v = v.clone();
+    v.trim();
+    1 + v.dec_len().max(0) as usize
+}
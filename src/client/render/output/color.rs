@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector2;
+use wgpu::{util::DeviceExt, PipelineCompilationOptions, ShaderStages};
+
+use crate::util::Gradient;
+
+use super::{
+    compute::HIST_LEN,
+    util::{Storage, Texture, Uniform},
+    ColorView, Coloring, Palette, CYCLE, PALETTE_LEN,
+};
+
+pub const COLOR_SHADER: &str = include_str!("color.wgsl");
+// the workgroup size in color.wgsl
+const WORKGROUP_SIZE: u32 = 8;
+
+// turns the raw iteration data from the compute shader into the output
+// texture every frame, so the palette can change without redoing any work
+pub struct ColorPipeline {
+    bind_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline: wgpu::ComputePipeline,
+    // sums up the histogram into cdf for equalized coloring
+    scan: wgpu::ComputePipeline,
+    cdf: Storage,
+    bind_group: wgpu::BindGroup,
+    pub output: Texture,
+    palette_texture: Texture,
+    coloring: Uniform<ColorView>,
+    // what the palette texture has, none until the first update
+    palette: Option<Palette>,
+}
+
+impl ColorPipeline {
+    pub fn init(
+        device: &wgpu::Device,
+        size: Vector2<u32>,
+        raw: &Storage,
+        histogram: &Storage,
+    ) -> Self {
+        let desc = wgpu::TextureDescriptor {
+            label: Some("color output"),
+            size: wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        };
+        let output = Texture::init(
+            device,
+            desc,
+            wgpu::TextureViewDescriptor::default(),
+            wgpu::SamplerDescriptor::default(),
+        );
+
+        let palette_texture = Texture::init(
+            device,
+            wgpu::TextureDescriptor {
+                label: Some("palette"),
+                size: wgpu::Extent3d {
+                    width: PALETTE_LEN,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                // one row, since gl can't do d1 textures
+                dimension: wgpu::TextureDimension::D2,
+                // only loaded, so the shader and the cpu renderer mix
+                // between texels the same way
+                format: wgpu::TextureFormat::Rgba32Float,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::TextureViewDescriptor::default(),
+            wgpu::SamplerDescriptor::default(),
+        );
+        let coloring = Uniform::init(device, "coloring");
+        let cdf = Storage::init_with(device, "cdf", &vec![0u8; HIST_LEN * 4]);
+
+        let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                raw.bind_group_layout_entry(0, true, ShaderStages::COMPUTE),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: output.format(),
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                coloring.bind_group_layout_entry(3),
+                histogram.bind_group_layout_entry(4, true, ShaderStages::COMPUTE),
+                cdf.bind_group_layout_entry(5, false, ShaderStages::COMPUTE),
+            ],
+            label: Some("color"),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("color"),
+            bind_group_layouts: &[&bind_layout],
+            push_constant_ranges: &[],
+        });
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_layout,
+            raw,
+            histogram,
+            &output,
+            &palette_texture,
+            &coloring,
+            &cdf,
+        );
+        let shader = Self::shader(device, COLOR_SHADER);
+        Self {
+            pipeline: Self::create_pipeline(device, &pipeline_layout, &shader, "main"),
+            scan: Self::create_pipeline(device, &pipeline_layout, &shader, "scan"),
+            cdf,
+            bind_layout,
+            pipeline_layout,
+            bind_group,
+            output,
+            palette_texture,
+            coloring,
+            palette: None,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+        coloring: &Coloring,
+    ) {
+        if self.palette.as_ref() != Some(&coloring.palette) {
+            self.set_palette(device, encoder, &coloring.palette.gradient);
+            self.palette = Some(coloring.palette.clone());
+        }
+        self.coloring
+            .update(device, encoder, belt, ColorView::from(coloring));
+    }
+
+    pub fn run(&self, encoder: &mut wgpu::CommandEncoder) {
+        let size = self.output.texture.size();
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_pipeline(&self.scan);
+        pass.dispatch_workgroups(1, 1, 1);
+        pass.set_pipeline(&self.pipeline);
+        pass.dispatch_workgroups(
+            size.width.div_ceil(WORKGROUP_SIZE),
+            size.height.div_ceil(WORKGROUP_SIZE),
+            1,
+        );
+    }
+
+    // raw is replaced whenever the size changes
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        size: Vector2<u32>,
+        raw: &Storage,
+        histogram: &Storage,
+    ) {
+        self.output.resize(
+            device,
+            wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.bind_layout,
+            raw,
+            histogram,
+            &self.output,
+            &self.palette_texture,
+            &self.coloring,
+            &self.cdf,
+        );
+    }
+
+    // keeps the last pipeline if the new one doesn't compile, like the
+    // compute shader
+    pub fn set_source(&mut self, device: &wgpu::Device, source: &str) {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = Self::shader(device, source);
+        let pipeline = Self::create_pipeline(device, &self.pipeline_layout, &shader, "main");
+        let scan = Self::create_pipeline(device, &self.pipeline_layout, &shader, "scan");
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(e) => println!("shader error, keeping the last pipeline:\n{}", e),
+            None => {
+                self.pipeline = pipeline;
+                self.scan = scan;
+            }
+        }
+    }
+
+    fn set_palette(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        gradient: &Gradient,
+    ) {
+        let texels = gradient.texels(PALETTE_LEN as usize);
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("palette upload"),
+            contents: bytemuck::cast_slice(&texels),
+            usage: wgpu::BufferUsages::COPY_SRC,
+        });
+        encoder.copy_buffer_to_texture(
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: None,
+                    rows_per_image: None,
+                },
+            },
+            self.palette_texture.texture.as_image_copy(),
+            wgpu::Extent3d {
+                width: PALETTE_LEN,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        raw: &Storage,
+        histogram: &Storage,
+        output: &Texture,
+        palette: &Texture,
+        coloring: &Uniform<ColorView>,
+        cdf: &Storage,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                raw.bind_group_entry(0),
+                output.view_bind_group_entry(1),
+                palette.view_bind_group_entry(2),
+                coloring.bind_group_entry(3),
+                histogram.bind_group_entry(4),
+                cdf.bind_group_entry(5),
+            ],
+            label: Some("color"),
+        })
+    }
+
+    fn shader(device: &wgpu::Device, source: &str) -> wgpu::ShaderModule {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("color"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        })
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        entry_point: &str,
+    ) -> wgpu::ComputePipeline {
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(entry_point),
+            layout: Some(layout),
+            entry_point: Some(entry_point),
+            module: shader,
+            cache: None,
+            compilation_options: PipelineCompilationOptions {
+                constants: &HashMap::from([("CYCLE".to_string(), CYCLE as f64)]),
+                ..Default::default()
+            },
+        })
+    }
+}
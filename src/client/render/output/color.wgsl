@@ -0,0 +1,194 @@
+// set from Coloring in palette.rs; iterations per loop of the palette at
+// density 1
+override CYCLE: f32;
+
+// Shading in palette.rs
+const SHADE_LINES: u32 = 1u;
+const SHADE_DISTANCE: u32 = 2u;
+const SHADE_LAMBERT: u32 = 3u;
+const SHADE_NORMAL_MAP: u32 = 4u;
+// pixels closer to the set than this get darkened into the lines
+const LINE_WIDTH: f32 = 1.0;
+// doublings of the distance it takes to loop the palette at density 1
+const DIST_CYCLE: f32 = 16.0;
+// keeps log2 finite when the estimate underflows
+const MIN_DIST: f32 = 1e-6;
+// the light comes from the top right
+const LIGHT: vec2<f32> = vec2(0.70710677, 0.70710677);
+// for Lambert, the surface is 45 degrees steep this many pixels from the set
+// and flattens out further away
+const RELIEF: f32 = 4.0;
+// for NormalMap, how far above the surface the light is
+const HEIGHT: f32 = 1.5;
+// HIST_BITS in compute/data.rs
+const HIST_BITS: u32 = 14u;
+
+// written by the compute shader, like Raw in compute/shader.wgsl
+struct Raw {
+    escaped: u32,
+    done: u32,
+    iters: u32,
+    normalized: f32,
+    abs_z: f32,
+    de: f32,
+    normal: vec2<f32>,
+}
+
+struct Coloring {
+    offset: f32,
+    density: f32,
+    normalized: u32,
+    equalize: u32,
+    shading: u32,
+}
+
+@group(0) @binding(0)
+var<storage> raw: array<Raw>;
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba8unorm, write>;
+// a row of texels; 1d textures don't work on gl
+@group(0) @binding(2)
+var palette: texture_2d<f32>;
+@group(0) @binding(3)
+var<uniform> coloring: Coloring;
+// pixels that escaped in each bin of iterations, counted by the compute shader
+@group(0) @binding(4)
+var<storage> histogram: array<u32>;
+// the running total of histogram, from scan
+@group(0) @binding(5)
+var<storage, read_write> cdf: array<u32>;
+
+// x loops through the palette, linear between texels and looping back
+// around at the end
+fn palette_color(x: f32) -> vec3<f32> {
+    let len = textureDimensions(palette).x;
+    let t = fract(x) * f32(len);
+    let i = u32(t);
+    let f = t - f32(i);
+    let a = textureLoad(palette, vec2(i % len, 0u), 0).rgb;
+    let b = textureLoad(palette, vec2((i + 1u) % len, 0u), 0).rgb;
+    return a + (b - a) * f;
+}
+
+// hist_bin in compute/shader.wgsl
+fn hist_bin(i: u32) -> u32 {
+    if i < (1u << HIST_BITS) {
+        return i;
+    }
+    let e = firstLeadingBit(i);
+    let mant = (i >> (e - HIST_BITS)) & ((1u << HIST_BITS) - 1u);
+    return ((e - HIST_BITS + 1u) << HIST_BITS) + mant;
+}
+
+// the first iteration in bin and how many it covers
+fn bin_range(bin: u32) -> vec2<u32> {
+    if bin < (1u << HIST_BITS) {
+        return vec2(bin, 1u);
+    }
+    let sh = (bin >> HIST_BITS) - 1u;
+    let mant = bin & ((1u << HIST_BITS) - 1u);
+    return vec2(((1u << HIST_BITS) + mant) << sh, 1u << sh);
+}
+
+// the fraction of escaped pixels that escaped before n, so the palette is
+// spread evenly over however many there are, linear within a bin
+fn equalized(n: f32) -> f32 {
+    let len = arrayLength(&cdf);
+    let total = cdf[len - 1u];
+    if total == 0u {
+        return 0.0;
+    }
+    let bin = min(hist_bin(u32(max(n, 0.0))), len - 1u);
+    var before = 0u;
+    if bin > 0u {
+        before = cdf[bin - 1u];
+    }
+    let range = bin_range(bin);
+    let f = clamp((n - f32(range.x)) / f32(range.y), 0.0, 1.0);
+    return (f32(before) + f * f32(cdf[bin] - before)) / f32(total);
+}
+
+// what the distance estimate multiplies the palette color by
+fn shade(r: Raw) -> f32 {
+    switch coloring.shading {
+        case SHADE_LINES: {
+            return min(r.de / LINE_WIDTH, 1.0);
+        }
+        case SHADE_LAMBERT: {
+            // the normal tips up as the surface flattens out; the light is
+            // 45 degrees up
+            let n = vec3(r.normal, min(r.de, 1e6) / RELIEF);
+            let light = vec3(LIGHT * 0.70710677, 0.70710677);
+            return max(dot(n, light) * inverseSqrt(dot(n, n)), 0.0);
+        }
+        case SHADE_NORMAL_MAP: {
+            return max((dot(r.normal, LIGHT) + HEIGHT) / (1.0 + HEIGHT), 0.0);
+        }
+        default: {
+            return 1.0;
+        }
+    }
+}
+
+const SCAN_SIZE: u32 = 256;
+
+var<workgroup> partial: array<u32, SCAN_SIZE>;
+
+// one workgroup, each thread sums its own stretch of the histogram and then
+// adds the stretches before it
+@compute @workgroup_size(SCAN_SIZE, 1, 1)
+fn scan(
+    @builtin(local_invocation_index) li: u32
+) {
+    let per = arrayLength(&histogram) / SCAN_SIZE;
+    let start = li * per;
+    var sum = 0u;
+    for (var j = 0u; j < per; j += 1u) {
+        sum += histogram[start + j];
+    }
+    partial[li] = sum;
+    workgroupBarrier();
+    var total = 0u;
+    for (var j = 0u; j < li; j += 1u) {
+        total += partial[j];
+    }
+    for (var j = 0u; j < per; j += 1u) {
+        total += histogram[start + j];
+        cdf[start + j] = total;
+    }
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>
+) {
+    let dims = textureDimensions(output);
+    if id.x >= dims.x || id.y >= dims.y {
+        return;
+    }
+    let r = raw[id.x * dims.y + id.y];
+    var color = vec3<f32>(0.0, 0.0, 0.0);
+    if bool(r.escaped) {
+        var n = f32(r.iters);
+        if bool(coloring.normalized) {
+            n = r.normalized;
+        }
+        // the estimate is negative for formulas that don't have one
+        let has_de = r.de >= 0.0;
+        if has_de && coloring.shading == SHADE_DISTANCE {
+            let x = log2(max(r.de, MIN_DIST)) * coloring.density / DIST_CYCLE;
+            color = palette_color(x + coloring.offset);
+        } else if bool(coloring.equalize) {
+            color = palette_color(equalized(n) * coloring.density + coloring.offset);
+        } else {
+            color = palette_color(n * coloring.density / CYCLE + coloring.offset);
+        }
+        if has_de {
+            color *= shade(r);
+        }
+    }
+    // alpha marks the pixels that are finished, since a finished one can
+    // still be black, like the middle of a boundary line or a done pixel
+    let finished = bool(r.escaped) || bool(r.done);
+    textureStore(output, id.xy, vec4(color, f32(finished)));
+}
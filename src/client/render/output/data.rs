@@ -2,37 +2,69 @@ use nalgebra::Vector2;
 
 use super::Camera;
 
+// has to match the array in shader.wgsl
+pub const MAX_SNAPSHOTS: usize = 16;
+// snapshots fade out over this many zooms by 2 away from the camera
+pub const FADE_LEVELS: f64 = 8.0;
+
 #[repr(C, align(8))]
 #[derive(Clone, Copy, Default, PartialEq)]
-pub struct WindowView {
+pub struct SnapshotView {
     pub stretch: Vector2<f32>,
     pub pos: Vector2<f32>,
-    pub snapshot: u32,
+    pub layer: u32,
+    pub alpha: f32,
+}
+
+#[repr(C, align(8))]
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct WindowView {
+    pub len: u32,
+    _pad: u32,
+    // closest zoom wise first, the shader uses the first one that covers a
+    // pixel
+    pub snapshots: [SnapshotView; MAX_SNAPSHOTS],
 }
 
 unsafe impl bytemuck::Pod for WindowView {}
 unsafe impl bytemuck::Zeroable for WindowView {}
 
+impl SnapshotView {
+    fn new(camera: &Camera, ss_cam: &Camera, layer: u32, alpha: f32) -> Self {
+        let s_mult = camera.stretch().component_div(&ss_cam.stretch());
+        let aspect = camera.inv_stretch().component_mul(&s_mult) * 2.0;
+        let s = s_mult * f32::from(camera.zoom.mult() * ss_cam.zoom.inv_mult());
+        Self {
+            pos: ((&camera.pos - &ss_cam.pos) * ss_cam.zoom.inv_mult().clone())
+                .map(f32::from)
+                .component_mul(&aspect),
+            stretch: s,
+            layer,
+            alpha,
+        }
+    }
+}
+
 impl WindowView {
-    pub fn from_camera(camera: &Camera, ss_cam: Option<&Camera>, snapshot: bool) -> Self {
-        let (pos, stretch) = if let Some(ss_cam) = ss_cam {
-            let s_mult = camera.stretch().component_div(&ss_cam.stretch());
-            let aspect = camera.inv_stretch().component_mul(&s_mult) * 2.0;
-            let s = s_mult * f32::from(camera.zoom.mult() * ss_cam.zoom.inv_mult());
-            (
-                ((&camera.pos - &ss_cam.pos) * ss_cam.zoom.inv_mult().clone())
-                    .map(f32::from)
-                    .component_mul(&aspect),
-                s,
-            )
-        } else {
-            (Vector2::default(), Vector2::default())
-        };
+    pub fn from_camera<'a>(
+        camera: &Camera,
+        snapshots: impl Iterator<Item = (&'a Camera, u32)>,
+    ) -> Self {
+        let mut near: Vec<_> = snapshots
+            .map(|(ss_cam, layer)| {
+                let levels = (camera.zoom.log2_mult() - ss_cam.zoom.log2_mult()).abs();
+                (levels, ss_cam, layer)
+            })
+            .filter(|(levels, ..)| *levels < FADE_LEVELS)
+            .collect();
+        near.sort_by(|a, b| a.0.total_cmp(&b.0));
 
-        Self {
-            pos,
-            stretch,
-            snapshot: snapshot as u32,
+        let mut res = Self::default();
+        for (view, (levels, ss_cam, layer)) in res.snapshots.iter_mut().zip(&near) {
+            let alpha = (1.0 - levels / FADE_LEVELS) as f32;
+            *view = SnapshotView::new(camera, ss_cam, *layer, alpha);
         }
+        res.len = near.len().min(MAX_SNAPSHOTS) as u32;
+        res
     }
 }
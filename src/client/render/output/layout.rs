@@ -11,16 +11,13 @@ pub struct Layout {
     format: wgpu::TextureFormat,
     pub view: Storage,
     pub chunks: ResizableTexture,
-    pub snapshot: Texture,
+    pub snapshots: Texture,
 }
 
 pub const LABEL: &str = file!();
 
 impl Layout {
-    pub fn init(
-        device: &wgpu::Device,
-        config: &wgpu::SurfaceConfiguration,
-    ) -> Self {
+    pub fn init(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
         let view = Storage::init_with(device, "view", bytemuck::bytes_of(&WindowView::default()));
 
         let texture_desc = wgpu::TextureDescriptor {
@@ -44,12 +41,13 @@ impl Layout {
         };
         let chunks = ResizableTexture::new(device, texture_desc, view_desc);
 
+        // resized to the output with a layer per snapshot when one's taken
         let desc = wgpu::TextureDescriptor {
-            label: Some("compute output"),
+            label: Some("snapshots"),
             size: wgpu::Extent3d {
                 width: config.width,
                 height: config.height,
-                depth_or_array_layers: 1,
+                depth_or_array_layers: 2,
             },
             mip_level_count: 1,
             sample_count: 1,
@@ -60,10 +58,13 @@ impl Layout {
                 | wgpu::TextureUsages::COPY_DST,
             view_formats: &[],
         };
-        let snapshot = Texture::init(
+        let snapshots = Texture::init(
             device,
             desc,
-            wgpu::TextureViewDescriptor::default(),
+            wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                ..Default::default()
+            },
             wgpu::SamplerDescriptor::default(),
         );
 
@@ -106,7 +107,7 @@ impl Layout {
                         visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::VERTEX,
                         ty: wgpu::BindingType::Texture {
                             multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
+                            view_dimension: wgpu::TextureViewDimension::D2Array,
                             sample_type: wgpu::TextureSampleType::Float { filterable: true },
                         },
                         count: None,
@@ -134,7 +135,7 @@ impl Layout {
             render_bind_layout,
             render_pipeline_layout,
             format: config.format,
-            snapshot,
+            snapshots,
         }
     }
 
@@ -146,8 +147,8 @@ impl Layout {
                 self.chunks.view_entry(1),
                 input.view_bind_group_entry(2),
                 input.sampler_bind_group_entry(3),
-                self.snapshot.view_bind_group_entry(4),
-                self.snapshot.sampler_bind_group_entry(5),
+                self.snapshots.view_bind_group_entry(4),
+                self.snapshots.sampler_bind_group_entry(5),
             ],
             label: Some(LABEL),
         })
@@ -1,12 +1,16 @@
 use std::ops::{Deref, DerefMut};
 use wgpu::include_wgsl;
 
+mod color;
 mod data;
 mod layout;
+mod palette;
 
 use super::{util::Texture, *};
+pub use color::*;
 pub use data::*;
 use layout::*;
+pub use palette::*;
 
 pub struct RenderPipeline {
     layout: Layout,
@@ -15,6 +19,14 @@ pub struct RenderPipeline {
 }
 
 const SHADER: wgpu::ShaderModuleDescriptor<'_> = include_wgsl!("shader.wgsl");
+// the most memory snapshots can take together
+const SNAPSHOT_BUDGET: u64 = 256 << 20;
+
+// how many snapshots of a window this size fit in the budget
+pub fn snapshot_capacity(size: Vector2<u32>) -> u32 {
+    let bytes = size.x as u64 * size.y as u64 * 4;
+    (SNAPSHOT_BUDGET / bytes.max(1)).clamp(2, MAX_SNAPSHOTS as u64) as u32
+}
 
 impl RenderPipeline {
     pub fn init(
@@ -31,6 +43,7 @@ impl RenderPipeline {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         device: &wgpu::Device,
@@ -38,13 +51,12 @@ impl RenderPipeline {
         belt: &mut wgpu::util::StagingBelt,
         view: &WindowView,
         input: &Texture,
-        snapshot: bool,
+        snapshot: Option<u32>,
+        capacity: u32,
     ) {
-        if snapshot {
+        if let Some(layer) = snapshot {
             let size = input.texture.size();
-            if self.snapshot.texture.size() != size {
-                self.snapshot.resize(device, size);
-            }
+            self.fit_snapshots(device, size, capacity);
             encoder.copy_texture_to_texture(
                 wgpu::TexelCopyTextureInfoBase {
                     texture: &input.texture,
@@ -53,9 +65,13 @@ impl RenderPipeline {
                     aspect: wgpu::TextureAspect::All,
                 },
                 wgpu::TexelCopyTextureInfoBase {
-                    texture: &self.snapshot.texture,
+                    texture: &self.snapshots.texture,
                     mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer,
+                    },
                     aspect: wgpu::TextureAspect::All,
                 },
                 size,
@@ -66,6 +82,59 @@ impl RenderPipeline {
             .update(device, encoder, belt, bytemuck::bytes_of(view));
     }
 
+    // puts a saved snapshot in, as linear rgba rows from the bottom up
+    pub fn write_snapshot(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        input: &Texture,
+        layer: u32,
+        capacity: u32,
+        data: &[u8],
+    ) {
+        let size = input.texture.size();
+        if self.fit_snapshots(device, size, capacity) {
+            self.bind_group = self.bind_group(device, input);
+        }
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.snapshots.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: layer,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(size.width * 4),
+                rows_per_image: None,
+            },
+            size,
+        );
+    }
+
+    // makes the snapshot layers the size of the output, which loses them
+    fn fit_snapshots(
+        &mut self,
+        device: &wgpu::Device,
+        size: wgpu::Extent3d,
+        capacity: u32,
+    ) -> bool {
+        let layers = wgpu::Extent3d {
+            depth_or_array_layers: capacity,
+            ..size
+        };
+        if self.snapshots.texture.size() == layers {
+            return false;
+        }
+        self.snapshots.resize(device, layers);
+        true
+    }
+
     pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, output: &wgpu::SurfaceTexture) {
         let view = output
             .texture
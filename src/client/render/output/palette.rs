@@ -0,0 +1,213 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
+
+use crate::util::Gradient;
+
+// texels in the palette texture
+pub const PALETTE_LEN: u32 = 1024;
+// iterations the palette takes to loop at density 1, the same as the old hue
+// rotation
+#[allow(clippy::approx_constant)]
+pub const CYCLE: f32 = 60.0 * 3.1415;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Palette {
+    pub name: String,
+    pub gradient: Gradient,
+}
+
+impl Palette {
+    pub fn builtin() -> Vec<Self> {
+        let srgb = |name: &str, stops: &[(f32, [u8; 3])]| Self {
+            name: name.to_string(),
+            gradient: Gradient::from_srgb(stops).unwrap(),
+        };
+        vec![
+            Self {
+                name: "hue".to_string(),
+                gradient: Gradient::hue(),
+            },
+            srgb(
+                "ultra",
+                &[
+                    (0.0, [0, 7, 100]),
+                    (0.16, [32, 107, 203]),
+                    (0.42, [237, 255, 255]),
+                    (0.6425, [255, 170, 0]),
+                    (0.8575, [0, 2, 0]),
+                ],
+            ),
+            srgb(
+                "fire",
+                &[
+                    (0.0, [0, 0, 0]),
+                    (0.3, [160, 20, 0]),
+                    (0.55, [255, 160, 0]),
+                    (0.7, [255, 255, 200]),
+                ],
+            ),
+            srgb("gray", &[(0.0, [0, 0, 0]), (0.5, [255, 255, 255])]),
+        ]
+    }
+
+    // every .map and .ugr in dir, sorted by file name; the ones that don't
+    // parse are skipped with their error printed
+    pub fn load_dir(dir: &Path) -> Vec<Self> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "map" || e == "ugr"))
+            .collect();
+        paths.sort();
+        paths.iter().flat_map(|p| Self::load(p)).collect()
+    }
+
+    // a .ugr can have more than one
+    pub fn load(path: &Path) -> Vec<Self> {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                println!("{}: {}", path.display(), e);
+                return Vec::new();
+            }
+        };
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let res = if path.extension().is_some_and(|e| e == "ugr") {
+            Gradient::parse_ugr(&source).map(|gradients| {
+                gradients
+                    .into_iter()
+                    .map(|(name, gradient)| Self {
+                        name: format!("{}/{}", stem, name),
+                        gradient,
+                    })
+                    .collect()
+            })
+        } else {
+            Gradient::parse_map(&source).map(|gradient| {
+                vec![Self {
+                    name: stem,
+                    gradient,
+                }]
+            })
+        };
+        res.unwrap_or_else(|e| {
+            println!("{}: {}", path.display(), e);
+            Vec::new()
+        })
+    }
+}
+
+impl Display for Palette {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+// what the distance estimate does on top of the palette; formulas without one
+// just get the palette
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Shading {
+    #[default]
+    None,
+    // darkens the pixels within a pixel of the set into thin lines
+    Lines,
+    // colors by how far away the set is instead of by iterations
+    Distance,
+    // lights the palette as if the set was a surface with the normal
+    Lambert,
+    // the same with the normal straight from the estimate, which looks more
+    // embossed
+    NormalMap,
+}
+
+impl Shading {
+    pub const ALL: [Self; 5] = [
+        Self::None,
+        Self::Lines,
+        Self::Distance,
+        Self::Lambert,
+        Self::NormalMap,
+    ];
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+}
+
+impl Display for Shading {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::None => "none",
+            Self::Lines => "lines",
+            Self::Distance => "distance",
+            Self::Lambert => "lambert",
+            Self::NormalMap => "normal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// how the raw iteration data turns into colors; it's all done in a pass after
+// the compute shader, so changing it shows up on the very next frame
+#[derive(Clone, PartialEq, Debug)]
+pub struct Coloring {
+    pub smooth: bool,
+    // spreads the palette over the histogram of escape iterations instead of
+    // looping it every CYCLE iterations
+    pub equalize: bool,
+    pub palette: Palette,
+    // shifts the palette along, in loops
+    pub offset: f32,
+    // how many times faster than CYCLE the palette loops, or how many loops
+    // it takes over all the escaped pixels when equalized
+    pub density: f32,
+    pub shading: Shading,
+}
+
+impl Default for Coloring {
+    fn default() -> Self {
+        Self {
+            smooth: true,
+            equalize: false,
+            palette: Palette::builtin().swap_remove(0),
+            offset: 0.0,
+            density: 1.0,
+            shading: Shading::None,
+        }
+    }
+}
+
+// Coloring's numbers, like the Coloring struct in color.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct ColorView {
+    pub offset: f32,
+    pub density: f32,
+    // whether to use the normalized iteration count
+    pub normalized: u32,
+    pub equalize: u32,
+    // Shading as a number, in the order it's declared
+    pub shading: u32,
+}
+
+unsafe impl bytemuck::Pod for ColorView {}
+unsafe impl bytemuck::Zeroable for ColorView {}
+
+impl From<&Coloring> for ColorView {
+    fn from(coloring: &Coloring) -> Self {
+        Self {
+            offset: coloring.offset,
+            density: coloring.density,
+            normalized: coloring.smooth as u32,
+            equalize: coloring.equalize as u32,
+            shading: coloring.shading as u32,
+        }
+    }
+}
@@ -1,7 +1,13 @@
+struct Snapshot {
+    stretch: vec2<f32>,
+    pos: vec2<f32>,
+    layer: u32,
+    alpha: f32,
+}
+
 struct View {
-    ss_stretch: vec2<f32>,
-    ss_pos: vec2<f32>,
-    snapshot: u32,
+    len: u32,
+    snapshots: array<Snapshot, 16>,
 }
 
 @group(0) @binding(0)
@@ -14,14 +20,14 @@ var tex: texture_2d<f32>;
 @group(0) @binding(3)
 var sam: sampler;
 @group(0) @binding(4)
-var ss_t: texture_2d<f32>;
+var ss_t: texture_2d_array<f32>;
 @group(0) @binding(5)
 var ss_s: sampler;
 
 struct VertexOutput {
     @builtin(position) vertex_pos: vec4<f32>,
     @location(0) tex_pos: vec2<f32>,
-    @location(1) ss_pos: vec2<f32>,
+    @location(1) vpos: vec2<f32>,
 };
 
 @vertex
@@ -39,8 +45,7 @@ fn vs_main(
     out.vertex_pos = vec4<f32>(vpos, 0.0, 1.0);
 
     out.tex_pos = tpos;
-    out.ss_pos = vpos * view.ss_stretch + view.ss_pos;
-    out.ss_pos = (out.ss_pos + 1.0) / 2.0;
+    out.vpos = vpos;
 
     return out;
 }
@@ -50,12 +55,17 @@ fn fs_main(
     in: VertexOutput,
 ) -> @location(0) vec4<f32> {
     let cur = textureSample(tex, sam, in.tex_pos);
-    let snp_bounds = all(in.ss_pos >= vec2(0.0)) && all(in.ss_pos <= vec2(1.0));
-    if all(cur.rgb == vec3(0.0)) && snp_bounds {
-        let snp = textureSample(ss_t, ss_s, in.ss_pos).rgb;
-        return vec4(snp * 0.3, 1.0);
-    } else {
+    if cur.a != 0.0 {
         return cur;
     }
+    // undecided, so show the closest snapshot that has this spot
+    for (var i = 0u; i < view.len; i++) {
+        let ss = view.snapshots[i];
+        let ss_pos = (in.vpos * ss.stretch + ss.pos + 1.0) / 2.0;
+        if all(ss_pos >= vec2(0.0)) && all(ss_pos <= vec2(1.0)) {
+            let snp = textureSampleLevel(ss_t, ss_s, ss_pos, ss.layer, 0.0).rgb;
+            return vec4(snp * 0.3 * ss.alpha, 1.0);
+        }
+    }
+    return vec4(cur.rgb, 1.0);
 }
-
@@ -0,0 +1,320 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, TryRecvError},
+};
+
+use nalgebra::Vector2;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::{client::bookmark::Bookmark, util::FixedDec};
+
+use super::{headless::linear, output::FADE_LEVELS, Camera, Formula};
+
+#[cfg(test)]
+mod test;
+
+// what's next to each image, the view it was taken at
+#[derive(Serialize, Deserialize)]
+struct Sidecar {
+    width: u32,
+    height: u32,
+    #[serde(flatten)]
+    view: Bookmark,
+}
+
+struct Entry {
+    image: PathBuf,
+    view: Bookmark,
+    camera: Camera,
+    julia: Option<Vector2<FixedDec>>,
+}
+
+// an image being decoded and scaled off the render thread
+struct Pending {
+    i: usize,
+    size: Vector2<u32>,
+    receiver: Receiver<Result<Vec<u8>, String>>,
+}
+
+// what the entries near a camera depend on, besides the entries themselves
+#[derive(PartialEq)]
+struct NearKey {
+    pos: Vector2<FixedDec>,
+    level: i32,
+    exp: f32,
+    size: Vector2<u32>,
+    formula: String,
+    julia: Option<Vector2<FixedDec>>,
+}
+
+// the most the pngs in the cache dir can take up before the oldest ones get
+// deleted to make room
+const MAX_BYTES: u64 = 1 << 30;
+
+// manual snapshots, saved to snapshots/ in the cache dir as a png and a
+// toml with the exact camera, and brought back whenever you're near one
+pub struct SavedSnapshots {
+    dir: Option<PathBuf>,
+    // indices stay the same so the chunk view can tell which are loaded;
+    // ones that fail to load or get deleted are left as None
+    entries: Vec<Option<Entry>>,
+    // only one is read at a time, so a lot of them nearby can't back up
+    pending: Option<Pending>,
+    // the entries on screen for the last camera, closest first, so the
+    // FixedDec math isn't redone every frame. cleared when entries change
+    near: Option<(NearKey, Vec<(usize, f64)>)>,
+}
+
+impl SavedSnapshots {
+    pub fn load() -> Self {
+        Self::load_from(dirs::cache_dir().map(|d| d.join("fractal").join("snapshots")))
+    }
+
+    fn load_from(dir: Option<PathBuf>) -> Self {
+        let mut entries = Vec::new();
+        if let Some(Ok(files)) = dir.as_ref().map(std::fs::read_dir) {
+            for path in files.flatten().map(|f| f.path()) {
+                if path.extension().is_some_and(|e| e == "toml") {
+                    match read_sidecar(&path) {
+                        Ok(entry) => entries.push(Some(entry)),
+                        Err(e) => println!("{}: {}", path.display(), e),
+                    }
+                }
+            }
+        }
+        if !entries.is_empty() {
+            println!("{} saved snapshots", entries.len());
+        }
+        Self {
+            dir,
+            entries,
+            pending: None,
+            near: None,
+        }
+    }
+
+    // writes a snapshot given as rgba rows from the top down, returning
+    // which saved snapshot it is
+    pub fn save(
+        &mut self,
+        data: &[u8],
+        camera: &Camera,
+        formula: &Formula,
+        julia: Option<&Vector2<FixedDec>>,
+    ) -> Result<usize, String> {
+        let dir = self.dir.as_ref().ok_or("no cache dir")?;
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let name = (1..)
+            .map(|i| dir.join(i.to_string()))
+            .find(|n| !n.with_extension("toml").exists())
+            .unwrap();
+
+        let image = name.with_extension("png");
+        write_png(&image, data, camera.size).map_err(|e| format!("{}: {}", image.display(), e))?;
+        let sidecar = Sidecar {
+            width: camera.size.x,
+            height: camera.size.y,
+            view: Bookmark::new(camera, formula, julia),
+        };
+        let path = name.with_extension("toml");
+        let source = toml::to_string(&sidecar).map_err(|e| e.to_string())?;
+        std::fs::write(&path, source).map_err(|e| format!("{}: {}", path.display(), e))?;
+        println!("saved snapshot to {}", image.display());
+
+        self.entries.push(Some(Entry {
+            image,
+            view: sidecar.view,
+            camera: camera.clone(),
+            julia: julia.cloned(),
+        }));
+        self.trim(MAX_BYTES);
+        Ok(self.entries.len() - 1)
+    }
+
+    // deletes the oldest snapshots until the rest fit in max_bytes, never
+    // the one that was just saved
+    fn trim(&mut self, max_bytes: u64) {
+        self.near = None;
+        let mut files: Vec<_> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| {
+                let meta = std::fs::metadata(&e.as_ref()?.image).ok()?;
+                Some((meta.modified().ok()?, meta.len(), i))
+            })
+            .collect();
+        files.sort();
+        let mut total: u64 = files.iter().map(|f| f.1).sum();
+        for &(_, bytes, i) in &files[..files.len().saturating_sub(1)] {
+            if total <= max_bytes {
+                break;
+            }
+            let Some(entry) = self.entries[i].take() else {
+                continue;
+            };
+            for path in [entry.image.with_extension("toml"), entry.image] {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    println!("could not delete {}: {}", path.display(), e);
+                }
+            }
+            total -= bytes;
+        }
+    }
+
+    // the closest one zoom wise that overlaps the screen, with how many
+    // levels away it is, skipping ones that are already loaded
+    pub fn nearest(
+        &mut self,
+        camera: &Camera,
+        formula: &Formula,
+        julia: Option<&Vector2<FixedDec>>,
+        loaded: impl Fn(usize) -> bool,
+    ) -> Option<(usize, f64)> {
+        let key = NearKey {
+            pos: camera.pos.clone(),
+            level: camera.zoom.level(),
+            exp: camera.zoom.exp(),
+            size: camera.size,
+            formula: formula.to_string(),
+            julia: julia.cloned(),
+        };
+        if self.near.as_ref().is_none_or(|(k, _)| *k != key) {
+            let mut near: Vec<_> = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter_map(|(i, e)| Some((i, e.as_ref()?)))
+                .filter(|(_, e)| {
+                    e.view.formula == key.formula && same_julia(e.julia.as_ref(), julia)
+                })
+                .filter_map(|(i, e)| Some((i, near(camera, &e.camera)?)))
+                .collect();
+            near.sort_by(|a, b| a.1.total_cmp(&b.1));
+            self.near = Some((key, near));
+        }
+        let (_, near) = self.near.as_ref()?;
+        near.iter().copied().find(|(i, _)| !loaded(*i))
+    }
+
+    pub fn camera(&self, i: usize) -> Option<&Camera> {
+        self.entries[i].as_ref().map(|e| &e.camera)
+    }
+
+    // starts reading the image scaled to size on another thread, unless one
+    // is already being read
+    pub fn request(&mut self, i: usize, size: Vector2<u32>) {
+        let Some(entry) = self.entries[i].as_ref() else {
+            return;
+        };
+        if self.pending.is_some() {
+            return;
+        }
+        let image = entry.image.clone();
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            let res = read_png(&image)
+                .map(|(data, image_size)| fit(&data, image_size, size))
+                .map_err(|e| format!("{}: {}", image.display(), e));
+            let _ = sender.send(res);
+        });
+        self.pending = Some(Pending { i, size, receiver });
+    }
+
+    // a requested image in the texture's order and colors once it's read,
+    // as long as it's still for size
+    pub fn poll(&mut self, size: Vector2<u32>) -> Option<(usize, Vec<u8>)> {
+        let res = match self.pending.as_ref()?.receiver.try_recv() {
+            Ok(res) => res,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("the reader stopped".to_string()),
+        };
+        let pending = self.pending.take()?;
+        match res {
+            Ok(data) => (pending.size == size).then_some((pending.i, data)),
+            Err(e) => {
+                println!("{}", e);
+                self.entries[pending.i] = None;
+                self.near = None;
+                None
+            }
+        }
+    }
+}
+
+fn read_sidecar(path: &Path) -> Result<Entry, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let sidecar: Sidecar = toml::from_str(&source).map_err(|e| e.to_string())?;
+    let size = Vector2::new(sidecar.width, sidecar.height);
+    Ok(Entry {
+        image: path.with_extension("png"),
+        camera: sidecar.view.camera(size),
+        julia: sidecar.view.julia(),
+        view: sidecar.view,
+    })
+}
+
+fn same_julia(a: Option<&Vector2<FixedDec>>, b: Option<&Vector2<FixedDec>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => (&a.x - &b.x).is_zero() && (&a.y - &b.y).is_zero(),
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+// how many levels away the snapshot is if it's close enough zoom wise to
+// show and covers some of the screen
+fn near(camera: &Camera, ss_cam: &Camera) -> Option<f64> {
+    let levels = (camera.zoom.log2_mult() - ss_cam.zoom.log2_mult()).abs();
+    if levels >= FADE_LEVELS {
+        return None;
+    }
+    // in screens, the snapshot overlaps if its center is within half of
+    // both of them
+    let reach = 0.5 * (1.0 + (ss_cam.zoom.log2_mult() - camera.zoom.log2_mult()).exp2());
+    let offset = ((&camera.pos - &ss_cam.pos) * camera.zoom.inv_mult().clone()).map(f64::from);
+    (offset.x.abs() < reach && offset.y.abs() < reach).then_some(levels)
+}
+
+fn write_png(path: &Path, data: &[u8], size: Vector2<u32>) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), size.x, size.y);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut w| w.write_image_data(data))
+        .map_err(|e| e.to_string())
+}
+
+fn read_png(path: &Path) -> Result<(Vec<u8>, Vector2<u32>), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::ALPHA);
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).map_err(|e| e.to_string())?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return Err("expected an 8 bit rgba png".to_string());
+    }
+    data.truncate(info.buffer_size());
+    Ok((data, Vector2::new(info.width, info.height)))
+}
+
+// nearest neighbor scaled to size, flipped to go from the bottom up, and
+// back to linear like the output texture
+fn fit(data: &[u8], from: Vector2<u32>, size: Vector2<u32>) -> Vec<u8> {
+    let mut res = Vec::with_capacity((size.x * size.y * 4) as usize);
+    for y in (0..size.y).rev() {
+        let sy = (y as u64 * from.y as u64 / size.y as u64) as u32;
+        for x in 0..size.x {
+            let sx = (x as u64 * from.x as u64 / size.x as u64) as u32;
+            let i = ((sy * from.x + sx) * 4) as usize;
+            let px = &data[i..i + 4];
+            res.extend([linear(px[0]), linear(px[1]), linear(px[2]), px[3]]);
+        }
+    }
+    res
+}
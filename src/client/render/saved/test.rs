@@ -0,0 +1,141 @@
+use std::path::PathBuf;
+
+use nalgebra::Vector2;
+
+use crate::{
+    client::camera::{Camera, Zoom},
+    util::FixedDec,
+};
+
+use super::{super::Formula, fit, near, read_png, SavedSnapshots, FADE_LEVELS};
+
+// a dir of its own in the temp dir for each test, since they run at once
+fn temp(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fractal-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn camera(x: &str, level: i32) -> Camera {
+    Camera {
+        pos: Vector2::new(x.parse().unwrap(), "0.125".parse().unwrap()),
+        zoom: Zoom::new(level, 0.25),
+        size: Vector2::new(2, 1),
+    }
+}
+
+const IMAGE: [u8; 8] = [255, 0, 0, 255, 0, 0, 255, 255];
+
+#[test]
+fn sidecar_round_trip() {
+    let dir = temp("saved-round-trip");
+    let c = Vector2::new(FixedDec::from(-0.75), FixedDec::from(0.1015625));
+    let camera = camera("-1.7499576837060935036022145060706997072711", 60);
+    let mut saved = SavedSnapshots::load_from(Some(dir.clone()));
+    assert_eq!(
+        saved.save(&IMAGE, &camera, &Formula::Tricorn, Some(&c)),
+        Ok(0)
+    );
+
+    let mut back = SavedSnapshots::load_from(Some(dir.clone()));
+    assert_eq!(back.entries.len(), 1);
+    let entry = back.entries[0].as_ref().unwrap();
+    assert_eq!(entry.camera.pos, camera.pos);
+    assert_eq!(entry.camera.zoom.level(), camera.zoom.level());
+    assert_eq!(entry.camera.zoom.exp(), camera.zoom.exp());
+    assert_eq!(entry.camera.size, camera.size);
+    assert_eq!(entry.julia, Some(c.clone()));
+    assert_eq!(entry.view.formula, "tricorn");
+    assert_eq!(read_png(&entry.image), Ok((IMAGE.to_vec(), camera.size)));
+
+    let found = back.nearest(&camera, &Formula::Tricorn, Some(&c), |_| false);
+    assert_eq!(found, Some((0, 0.0)));
+    assert_eq!(
+        back.nearest(&camera, &Formula::Tricorn, Some(&c), |_| true),
+        None
+    );
+    assert_eq!(
+        back.nearest(&camera, &Formula::Mandelbrot, Some(&c), |_| false),
+        None
+    );
+    assert_eq!(
+        back.nearest(&camera, &Formula::Tricorn, None, |_| false),
+        None
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn nearby() {
+    let ss = camera("-0.5", 10);
+    assert_eq!(near(&ss, &ss), Some(0.0));
+    // zoomed in a level on its center, and then three quarters of one
+    assert_eq!(near(&camera("-0.5", 11), &ss), Some(1.0));
+    let deeper = Camera {
+        zoom: Zoom::new(11, 0.5),
+        ..ss.clone()
+    };
+    assert!((near(&deeper, &ss).unwrap() - 0.75).abs() < 1e-9);
+    let levels = FADE_LEVELS as i32;
+    assert_eq!(near(&camera("-0.5", 10 + levels), &ss), None);
+    assert_eq!(near(&camera("-0.5", 10 - levels), &ss), None);
+    // a whole screen over is past the edge of both
+    let screen = f64::from(ss.zoom.mult().clone());
+    let over = |screens: f64| Camera {
+        pos: Vector2::new(FixedDec::from(-0.5 + screens * screen), ss.pos.y.clone()),
+        ..ss.clone()
+    };
+    assert!(near(&over(0.9), &ss).is_some());
+    assert_eq!(near(&over(1.1), &ss), None);
+}
+
+#[test]
+fn fits() {
+    // red, green on top and blue, white on the bottom, scaled up to 4x2
+    let data = [
+        255, 0, 0, 255, 0, 255, 0, 128, //
+        0, 0, 255, 0, 255, 255, 255, 255,
+    ];
+    let fitted = fit(&data, Vector2::new(2, 2), Vector2::new(4, 2));
+    let px = |i: usize| &fitted[i * 4..i * 4 + 4];
+    // bottom up
+    assert_eq!(px(0), [0, 0, 255, 0]);
+    assert_eq!(px(1), [0, 0, 255, 0]);
+    assert_eq!(px(2), [255, 255, 255, 255]);
+    assert_eq!(px(4), [255, 0, 0, 255]);
+    assert_eq!(px(7), [0, 255, 0, 128]);
+    // scaled down, the first row and every other column of what it's given
+    let fitted = fit(&fitted, Vector2::new(4, 2), Vector2::new(2, 1));
+    assert_eq!(fitted, [0, 0, 255, 0, 255, 255, 255, 255]);
+}
+
+#[test]
+fn trims() {
+    let dir = temp("saved-trim");
+    let mut saved = SavedSnapshots::load_from(Some(dir.clone()));
+    for i in 0..3 {
+        let camera = camera("-0.5", i);
+        assert_eq!(
+            saved.save(&IMAGE, &camera, &Formula::Mandelbrot, None),
+            Ok(i as usize)
+        );
+    }
+    let files = |saved: &SavedSnapshots| {
+        let images: Vec<_> = saved.entries.iter().map(|e| e.is_some()).collect();
+        let on_disk = (1..=3).map(|i| dir.join(format!("{}.toml", i)).exists());
+        assert_eq!(images, on_disk.collect::<Vec<_>>());
+        images
+    };
+    assert_eq!(files(&saved), [true; 3]);
+    let bytes = std::fs::metadata(dir.join("1.png")).unwrap().len();
+    saved.trim(bytes * 2);
+    assert_eq!(files(&saved), [false, true, true]);
+    // the newest stays even when it doesn't fit
+    saved.trim(0);
+    assert_eq!(files(&saved), [false, false, true]);
+    assert_eq!(
+        saved.nearest(&camera("-0.5", 1), &Formula::Mandelbrot, None, |_| false),
+        Some((2, 1.0))
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
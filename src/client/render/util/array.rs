@@ -6,7 +6,7 @@ pub struct ArrayBuffer<T: bytemuck::Pod> {
     buffer: wgpu::Buffer,
     label: String,
     usage: BufferUsages,
-    update: Option<Vec<T>>,
+    update: Option<ArrBufUpdate<T>>,
 }
 
 impl<T: bytemuck::Pod> ArrayBuffer<T> {
@@ -36,24 +36,67 @@ impl<T: bytemuck::Pod> ArrayBuffer<T> {
         if self.len == 0 {
             return resized;
         }
-        if let Some(update) = self.update.take() {
+        if let Some(ArrBufUpdate { offset, data }) = self.update.take() {
+            if data.is_empty() {
+                return resized;
+            }
             let mut view = belt.write_buffer(
                 encoder,
                 &self.buffer,
-                0,
+                (offset * std::mem::size_of::<T>()) as BufferAddress,
                 unsafe {
-                    std::num::NonZeroU64::new_unchecked(std::mem::size_of_val(&update[..]) as u64)
+                    std::num::NonZeroU64::new_unchecked(std::mem::size_of_val(&data[..]) as u64)
                 },
                 device,
             );
-            view.copy_from_slice(bytemuck::cast_slice(&update));
+            view.copy_from_slice(bytemuck::cast_slice(&data));
         }
         resized
     }
 
+    // copies the buffer back, waiting for the gpu to finish with it
+    pub fn read(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Vec<T> {
+        let size = (self.len * std::mem::size_of::<T>()) as u64;
+        if size == 0 {
+            return Vec::new();
+        }
+        let map_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&(self.label.clone() + " readback")),
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            size,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &map_buf, 0, size);
+        queue.submit(Some(encoder.finish()));
+        let slice = map_buf.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |res| {
+            res.expect("Could not read buffer!")
+        });
+        device.poll(wgpu::Maintain::Wait);
+        let res = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        res
+    }
+
     pub fn set(&mut self, data: Vec<T>) {
-        self.new_len = data.len();
-        self.update = Some(data);
+        self.set_from(0, data);
+    }
+
+    // replaces everything from offset on, keeping what's before it, so a
+    // growing array only uploads what's new
+    pub fn set_from(&mut self, offset: usize, mut data: Vec<T>) {
+        let offset = match self.update.take() {
+            // not uploaded yet, so this goes on the end of it
+            Some(mut pending) if pending.offset <= offset => {
+                pending.data.truncate(offset - pending.offset);
+                pending.data.append(&mut data);
+                data = pending.data;
+                pending.offset
+            }
+            _ => offset,
+        };
+        self.new_len = offset + data.len();
+        self.update = Some(ArrBufUpdate { offset, data });
     }
 
     pub fn init(device: &wgpu::Device, label: &str, usage: BufferUsages) -> Self {
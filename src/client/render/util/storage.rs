@@ -57,6 +57,10 @@ impl Storage {
         }
     }
 
+    pub fn clear(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.clear_buffer(&self.buffer, 0, None);
+    }
+
     pub fn bind_group_layout_entry(
         &self,
         binding: u32,
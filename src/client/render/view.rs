@@ -1,12 +1,31 @@
+use nalgebra::Vector2;
+
 use crate::client::camera::Camera;
 
 use super::output::WindowView;
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SnapshotKind {
+    // these get saved to disk
+    Manual,
+    Auto,
+}
+
+pub struct Snapshot {
+    pub camera: Camera,
+    pub layer: u32,
+    // which saved snapshot this is, if it's on disk
+    pub saved: Option<usize>,
+}
+
 // TODO: move this out; this is not needed rn
 #[derive(Default)]
 pub struct ChunkView {
     pub render: WindowView,
-    pub snapshot: Option<Camera>,
+    // oldest first
+    pub snapshots: Vec<Snapshot>,
+    // of every layer
+    size: Vector2<u32>,
 }
 
 impl ChunkView {
@@ -14,15 +33,72 @@ impl ChunkView {
         Self::default()
     }
 
-    pub fn update(&mut self, camera: &Camera, snapshot: bool) {
-        if snapshot {
-            self.snapshot = Some(camera.clone());
-        }
-        let render = WindowView::from_camera(camera, self.snapshot.as_ref(), snapshot);
+    pub fn update(&mut self, camera: &Camera) {
+        self.render =
+            WindowView::from_camera(camera, self.snapshots.iter().map(|s| (&s.camera, s.layer)));
+    }
 
-        if self.render == render {
-            return;
+    // adds a snapshot taken at ss_cam and returns the layer it goes in. when
+    // every layer is used, the snapshot farthest away zoom wise from camera
+    // is replaced, the oldest of those if there's a tie
+    pub fn push(
+        &mut self,
+        camera: &Camera,
+        ss_cam: Camera,
+        saved: Option<usize>,
+        size: Vector2<u32>,
+        capacity: usize,
+    ) -> u32 {
+        if self.size != size {
+            // the layers are all one size, so they're lost when the window
+            // is resized
+            self.snapshots.clear();
+            self.size = size;
         }
-        self.render = render;
+        let layer = if self.snapshots.len() < capacity {
+            (0..)
+                .find(|l| self.snapshots.iter().all(|s| s.layer != *l))
+                .unwrap()
+        } else {
+            let (far, _) = self
+                .snapshots
+                .iter()
+                .enumerate()
+                .rev()
+                .max_by(|(_, a), (_, b)| levels(camera, a).total_cmp(&levels(camera, b)))
+                .unwrap();
+            self.snapshots.remove(far).layer
+        };
+        self.snapshots.push(Snapshot {
+            camera: ss_cam,
+            layer,
+            saved,
+        });
+        layer
     }
+
+    // whether a snapshot this many levels away from camera would get a
+    // layer without replacing a closer one
+    pub fn has_room(
+        &self,
+        camera: &Camera,
+        size: Vector2<u32>,
+        levels: f64,
+        capacity: usize,
+    ) -> bool {
+        self.size != size
+            || self.snapshots.len() < capacity
+            || self
+                .snapshots
+                .iter()
+                .any(|s| self::levels(camera, s) > levels)
+    }
+
+    pub fn has_saved(&self, saved: usize) -> bool {
+        self.snapshots.iter().any(|s| s.saved == Some(saved))
+    }
+}
+
+fn levels(camera: &Camera, snapshot: &Snapshot) -> f64 {
+    (snapshot.camera.zoom.log2_mult() - camera.zoom.log2_mult()).abs()
 }
@@ -0,0 +1,58 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// polls the modification times of the wgsl and formula files in a directory;
+// the first poll always counts as a change so everything gets loaded
+pub struct Watcher {
+    dir: PathBuf,
+    stamps: Option<Vec<(PathBuf, SystemTime)>>,
+    last_poll: Option<Instant>,
+}
+
+impl Watcher {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            stamps: None,
+            last_poll: None,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.is_some_and(|t| t.elapsed() < POLL_INTERVAL) {
+            return false;
+        }
+        self.last_poll = Some(Instant::now());
+        let stamps = self.stamps();
+        if self.stamps.as_ref() == Some(&stamps) {
+            return false;
+        }
+        self.stamps = Some(stamps);
+        true
+    }
+
+    fn stamps(&self) -> Vec<(PathBuf, SystemTime)> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut stamps: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                let path = e.path();
+                path.extension()
+                    .is_some_and(|ext| ext == "wgsl" || ext == "formula")
+            })
+            .filter_map(|e| Some((e.path(), e.metadata().ok()?.modified().ok()?)))
+            .collect();
+        stamps.sort();
+        stamps
+    }
+}
@@ -2,14 +2,71 @@
 #![feature(int_roundings)]
 #![feature(let_chains)]
 
-use client::ClientApp;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use client::{ClientApp, Location, RenderArgs};
 
 mod client;
 mod util;
 
+#[derive(Parser)]
+struct Args {
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Reload the compute shader and formulas from the .wgsl and .formula files in DIR when they change"
+    )]
+    watch: Option<PathBuf>,
+    #[arg(help = "Start at a location copied with ctrl+c, like fractal:v1:-0.8:0:0:0.5")]
+    location: Option<Location>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "location",
+        help = "Start at the view in a Kalles Fraktaler .kfr or XaoS .xpf file"
+    )]
+    open: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        conflicts_with_all = ["location", "open"],
+        help = "Carry on iterating from a checkpoint saved with ctrl+k, by default the last one"
+    )]
+    resume: Option<Option<PathBuf>>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Render an image to a png without opening a window")]
+    Render(RenderArgs),
+}
+
 fn main() {
+    let args = Args::parse();
+    if let Some(Command::Render(args)) = args.command {
+        if let Err(e) = client::render(args) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let resume = args.resume.map(|path| {
+        path.or_else(client::default_checkpoint).unwrap_or_else(|| {
+            eprintln!("no cache dir to find the last checkpoint in, give one with --resume FILE");
+            std::process::exit(1);
+        })
+    });
     let event_loop = winit::event_loop::EventLoop::new().expect("Failed to create event loop");
     event_loop
-        .run_app(&mut ClientApp::new())
+        .run_app(&mut ClientApp::new(
+            args.watch,
+            args.location,
+            args.open,
+            resume,
+        ))
         .expect("Failed to run event loop");
 }
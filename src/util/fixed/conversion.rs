@@ -125,3 +125,117 @@ impl From<i32> for FixedDec {
         }
     }
 }
+
+impl From<f64> for FixedDec {
+    fn from(value: f64) -> Self {
+        assert!(value.is_finite(), "cannot convert {} to FixedDec", value);
+        let raw = value.to_bits();
+        let exp = ((raw >> 52) & 0x7ff) as i32;
+        let frac = raw & ((1 << 52) - 1);
+        // value = mant * 2^(exp - 1075), with subnormals having no implicit bit
+        let (mant, exp) = if exp == 0 {
+            (frac, -1074)
+        } else {
+            (frac | (1 << 52), exp - 1075)
+        };
+        let mut res = Self::from(mant) >> -exp;
+        res.trim();
+        if value.is_sign_negative() {
+            res.negate();
+        }
+        res
+    }
+}
+
+impl From<FixedDec> for f64 {
+    fn from(value: FixedDec) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&FixedDec> for f64 {
+    fn from(value: &FixedDec) -> Self {
+        let sign = if value.is_neg() { 1 << 63 } else { 0 };
+        let Some(k) = value.parts.iter().position(|&p| p != 0) else {
+            return if value.is_pos() { 0.0 } else { -0.0 };
+        };
+        let lz = value.parts[k].leading_zeros();
+        // the value is in [2^exp, 2^(exp + 1))
+        let exp = 32 * (value.dec - 1 - k as i32) + 31 - lz as i32;
+        if exp > 1023 {
+            return f64::from_bits(sign | f64::INFINITY.to_bits());
+        }
+        let part = |i: usize| value.parts.get(i).copied().unwrap_or(0) as u128;
+        // top bit of the value ends up at bit 127
+        let bits = ((part(k) << 96) | (part(k + 1) << 64) | (part(k + 2) << 32)) << lz;
+        let sticky = value.parts.iter().skip(k + 3).any(|&p| p != 0);
+
+        // number of mantissa bits the result has room for, less for subnormals
+        let prec = 53 - (-1022 - exp).max(0);
+        if prec < 0 {
+            return f64::from_bits(sign);
+        }
+        let sh = 128 - prec as u32;
+        let mant = bits.checked_shr(sh).unwrap_or(0) as u64;
+        let rest = bits & (1u128 << (sh - 1) << 1).wrapping_sub(1);
+        let half = 1u128 << (sh - 1);
+        let round_up = rest > half || (rest == half && (sticky || mant & 1 == 1));
+        let mant = mant + round_up as u64;
+        // the implicit bit of normal numbers adds one to the exponent, and
+        // rounding up past the top of the mantissa carries into it
+        let res = if prec == 53 {
+            (((exp + 1022) as u64) << 52) + mant
+        } else {
+            mant
+        };
+        f64::from_bits(sign | res.min(f64::INFINITY.to_bits()))
+    }
+}
+
+impl From<u64> for FixedDec {
+    fn from(value: u64) -> Self {
+        let mut res = Self {
+            dec: 2,
+            sign: POS,
+            parts: vec![(value >> 32) as u32, value as u32],
+        };
+        res.trim();
+        res
+    }
+}
+
+impl From<i64> for FixedDec {
+    fn from(value: i64) -> Self {
+        let mut res = Self::from(value.unsigned_abs());
+        if value.is_negative() {
+            res.negate();
+        }
+        res
+    }
+}
+
+impl From<u128> for FixedDec {
+    fn from(value: u128) -> Self {
+        let mut res = Self {
+            dec: 4,
+            sign: POS,
+            parts: (0..4).rev().map(|i| (value >> (i * 32)) as u32).collect(),
+        };
+        res.trim();
+        res
+    }
+}
+
+impl FixedDec {
+    // log2 of the magnitude, which works way past where f64 under or
+    // overflows; only the leading 3 parts matter at f64 precision
+    pub fn log2(&self) -> f64 {
+        let Some(lead) = self.parts.iter().position(|&p| p != 0) else {
+            return f64::NEG_INFINITY;
+        };
+        let top = (0..3).fold(0.0, |acc, i| {
+            acc * 2f64.powi(32) + self.part((lead + i) as i32) as f64
+        });
+        top.log2() + ((self.dec - lead as i32 - 3) * 32) as f64
+    }
+}
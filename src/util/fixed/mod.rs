@@ -1,10 +1,12 @@
 mod conversion;
 mod op;
+mod string;
 #[cfg(test)]
 mod test;
+pub mod wgsl;
 
 use num_traits::Zero;
-use std::fmt::{Binary, Display};
+use std::fmt::Binary;
 
 const POS: bool = false;
 const NEG: bool = true;
@@ -147,12 +149,6 @@ impl FixedDec {
     }
 }
 
-impl Display for FixedDec {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", f32::from(self))
-    }
-}
-
 impl Binary for FixedDec {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.sign == NEG {
@@ -2,7 +2,7 @@ use super::*;
 
 use std::{
     cmp::Ordering,
-    ops::{Add, AddAssign, Mul, MulAssign, Neg, Shl, Shr, Sub, SubAssign},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Shl, Shr, Sub, SubAssign},
 };
 
 impl Zero for FixedDec {
@@ -273,6 +273,171 @@ impl MulAssign<FixedDec> for FixedDec {
     }
 }
 
+impl FixedDec {
+    // precision is the number of parts kept in the result, starting from
+    // the first nonzero one; anything past that is truncated towards zero
+    pub fn div_prec(&self, rhs: &FixedDec, precision: usize) -> FixedDec {
+        let (a, a_exp) = self.int_parts();
+        let (b, b_exp) = rhs.int_parts();
+        if b.is_empty() {
+            panic!("attempt to divide by zero");
+        }
+        if a.is_empty() {
+            return Self::zero();
+        }
+        // scale the numerator so the integer quotient has at least
+        // precision + 1 parts, then undo the scaling with dec
+        let shift = (precision + b.len() + 1).saturating_sub(a.len());
+        let mut num = vec![0; shift];
+        num.extend(a.iter().rev());
+        let den: Vec<u32> = b.iter().rev().copied().collect();
+        let mut parts = div_limbs(&num, &den);
+        parts.reverse();
+        let exp = a_exp - b_exp - shift as i32;
+        let mut res = Self {
+            sign: self.sign != rhs.sign,
+            dec: parts.len() as i32 + exp,
+            parts,
+        };
+        res.trim();
+        res.parts.truncate(precision);
+        res.trim();
+        res
+    }
+
+    pub fn recip(&self, precision: usize) -> FixedDec {
+        Self::one().div_prec(self, precision)
+    }
+
+    // the significant parts as a big endian integer, and the exponent
+    // (in parts) that it has to be multiplied by to get the value back
+    fn int_parts(&self) -> (&[u32], i32) {
+        let start = self.parts.iter().take_while(|&&x| x == 0).count();
+        let end = self.parts.len() - self.parts.iter().rev().take_while(|&&x| x == 0).count();
+        if start >= end {
+            return (&[], 0);
+        }
+        (&self.parts[start..end], self.dec - end as i32)
+    }
+
+    fn default_precision(&self, rhs: &FixedDec) -> usize {
+        self.int_parts().0.len().max(rhs.int_parts().0.len()) + 1
+    }
+}
+
+// long division of little endian integers (Knuth's algorithm D);
+// returns the quotient, little endian, and drops the remainder
+fn div_limbs(num: &[u32], den: &[u32]) -> Vec<u32> {
+    let n = den.len();
+    if num.len() < n {
+        return Vec::new();
+    }
+    let m = num.len() - n;
+    let mut q = vec![0u32; m + 1];
+    if n == 1 {
+        let d = den[0] as u64;
+        let mut rem = 0u64;
+        for i in (0..num.len()).rev() {
+            let cur = (rem << 32) | num[i] as u64;
+            q[i] = (cur / d) as u32;
+            rem = cur % d;
+        }
+        return q;
+    }
+    // normalize so the top bit of the divisor is set
+    let s = den[n - 1].leading_zeros();
+    let shl = |x: &[u32], len: usize| -> Vec<u32> {
+        (0..len)
+            .map(|i| {
+                let hi = x.get(i).copied().unwrap_or(0) << s;
+                let lo = x.get(i.wrapping_sub(1)).copied().unwrap_or(0) as u64 >> (32 - s);
+                hi | lo as u32
+            })
+            .collect()
+    };
+    let vn = shl(den, n);
+    let mut un = shl(num, num.len() + 1);
+    const B: u64 = 1 << 32;
+    for j in (0..=m).rev() {
+        let top = ((un[j + n] as u64) << 32) | un[j + n - 1] as u64;
+        let mut qhat = top / vn[n - 1] as u64;
+        let mut rhat = top % vn[n - 1] as u64;
+        while qhat >= B || qhat * vn[n - 2] as u64 > ((rhat << 32) | un[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += vn[n - 1] as u64;
+            if rhat >= B {
+                break;
+            }
+        }
+        let mut borrow: i64 = 0;
+        for i in 0..n {
+            let p = qhat * vn[i] as u64;
+            let t = un[i + j] as i64 - borrow - (p & 0xffffffff) as i64;
+            un[i + j] = t as u32;
+            borrow = (p >> 32) as i64 - (t >> 32);
+        }
+        let t = un[j + n] as i64 - borrow;
+        un[j + n] = t as u32;
+        q[j] = qhat as u32;
+        if t < 0 {
+            // qhat was one too big, add the divisor back
+            q[j] = q[j].wrapping_sub(1);
+            let mut carry = 0u64;
+            for i in 0..n {
+                let t = un[i + j] as u64 + vn[i] as u64 + carry;
+                un[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+    }
+    q
+}
+
+impl Div for &FixedDec {
+    type Output = FixedDec;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.div_prec(rhs, self.default_precision(rhs))
+    }
+}
+
+impl Div for FixedDec {
+    type Output = FixedDec;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        &self / &rhs
+    }
+}
+
+impl Div<&FixedDec> for FixedDec {
+    type Output = FixedDec;
+
+    fn div(self, rhs: &FixedDec) -> Self::Output {
+        &self / rhs
+    }
+}
+
+impl Div<FixedDec> for &FixedDec {
+    type Output = FixedDec;
+
+    fn div(self, rhs: FixedDec) -> Self::Output {
+        self / &rhs
+    }
+}
+
+impl DivAssign<&FixedDec> for FixedDec {
+    fn div_assign(&mut self, rhs: &FixedDec) {
+        *self = &*self / rhs
+    }
+}
+
+impl DivAssign<FixedDec> for FixedDec {
+    fn div_assign(&mut self, rhs: FixedDec) {
+        *self = &*self / rhs
+    }
+}
+
 fn mul_lmsb(x: u32, y: u32) -> (u32, u32) {
     let lsb = x.wrapping_mul(y);
     let a = x & 0xffff;
@@ -347,3 +512,85 @@ impl FixedDec {
         self
     }
 }
+
+impl FixedDec {
+    // exact result of sqrt truncated to precision parts; the wgsl sqrt does the
+    // same thing with precision = LEN, so they give the same bits
+    pub fn sqrt(&self, precision: usize) -> FixedDec {
+        assert!(
+            self.is_pos(),
+            "attempt to take the square root of a negative number"
+        );
+        let start = self.parts.iter().take_while(|&&x| x == 0).count();
+        if start == self.parts.len() {
+            return Self::zero();
+        }
+        let mut dec = self.dec - start as i32;
+        let mut rad = Vec::with_capacity(precision * 2);
+        if dec.rem_euclid(2) == 1 {
+            rad.push(0);
+            dec += 1;
+        }
+        rad.extend(&self.parts[start..]);
+        rad.resize(precision * 2, 0);
+        let mut res = Self {
+            sign: POS,
+            dec: dec / 2,
+            parts: isqrt_limbs(&rad),
+        };
+        res.trim();
+        res
+    }
+
+    pub fn powi(&self, exp: u32) -> FixedDec {
+        let mut res = Self::one();
+        let mut base = self.clone();
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res *= &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        res
+    }
+}
+
+// digit by digit square root of a big endian integer with an even number of
+// parts; the result has half as many parts
+fn isqrt_limbs(rad: &[u32]) -> Vec<u32> {
+    let len = rad.len() / 2;
+    let mut root = vec![0u32; len];
+    // rem < 2 * root + 1, so it only needs one more part than root
+    let mut rem = vec![0u32; len + 1];
+    let mut trial = vec![0u32; len + 1];
+    for i in 0..len * 32 {
+        let bits = (rad[i / 16] >> (30 - (i % 16) * 2)) & 0b11;
+        shl_limbs(&mut rem, 2, bits);
+        trial[0] = 0;
+        trial[1..].copy_from_slice(&root);
+        shl_limbs(&mut trial, 2, 1);
+        let bit = rem >= trial;
+        if bit {
+            let mut borrow = false;
+            for j in (0..len + 1).rev() {
+                (rem[j], borrow) = rem[j].borrowing_sub(trial[j], borrow);
+            }
+        }
+        shl_limbs(&mut root, 1, bit as u32);
+    }
+    root
+}
+
+// shifts a big endian integer left by less than 32 bits and ors in bits
+fn shl_limbs(x: &mut [u32], sh: u32, bits: u32) {
+    let mut carry = bits;
+    for p in x.iter_mut().rev() {
+        let next = *p >> (32 - sh);
+        *p = (*p << sh) | carry;
+        carry = next;
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    f64::consts::LOG2_10,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use num_traits::Zero;
+
+use super::{FixedDec, NEG, POS};
+
+const CHUNK: u32 = 1_000_000_000;
+const CHUNK_DIGITS: usize = 9;
+// numbers that start past this many powers of 10 either way would have
+// pow10 take forever, and no zoom gets anywhere near them; strings come from
+// the clipboard, cli and files
+const MAX_EXP10: u32 = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFixedDecError {
+    Empty,
+    InvalidDigit,
+    InvalidExponent,
+    ExponentTooLarge,
+}
+
+impl Display for ParseFixedDecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "cannot parse number from empty string"),
+            Self::InvalidDigit => write!(f, "invalid digit found in string"),
+            Self::InvalidExponent => write!(f, "invalid exponent found in string"),
+            Self::ExponentTooLarge => {
+                write!(f, "exponent is past 10^{} either way", MAX_EXP10)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseFixedDecError {}
+
+impl FromStr for FixedDec {
+    type Err = ParseFixedDecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sign, s) = match s.as_bytes().first() {
+            Some(b'-') => (NEG, &s[1..]),
+            Some(b'+') => (POS, &s[1..]),
+            _ => (POS, s),
+        };
+        let (mantissa, exp) = match s.find(['e', 'E']) {
+            Some(i) => {
+                let exp = s[i + 1..]
+                    .parse::<i32>()
+                    .map_err(|_| ParseFixedDecError::InvalidExponent)?;
+                (&s[..i], exp)
+            }
+            None => (s, 0),
+        };
+        let (whole, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if whole.is_empty() && frac.is_empty() {
+            return Err(ParseFixedDecError::Empty);
+        }
+        let digits = || whole.bytes().chain(frac.bytes());
+        if !digits().all(|b| b.is_ascii_digit()) {
+            return Err(ParseFixedDecError::InvalidDigit);
+        }
+
+        let digits: Vec<u8> = digits().map(|b| b - b'0').collect();
+        let sig_digits = digits.iter().skip_while(|&&d| d == 0).count();
+        if sig_digits == 0 {
+            return Ok(FixedDec::zero());
+        }
+        // the power of 10 of the first digit that isn't 0, so any number of
+        // digits after it are fine
+        let lead = exp as i64 + whole.len() as i64 - (digits.len() - sig_digits) as i64 - 1;
+        if lead.unsigned_abs() > MAX_EXP10 as u64 {
+            return Err(ParseFixedDecError::ExponentTooLarge);
+        }
+        // value = digits * 10^exp10
+        let exp10 = exp
+            .checked_sub(frac.len() as i32)
+            .ok_or(ParseFixedDecError::InvalidExponent)?;
+        let mut res = FixedDec::zero();
+        for chunk in digits.chunks(CHUNK_DIGITS) {
+            let val = chunk.iter().fold(0, |acc, &d| acc * 10 + d as u32);
+            res = res * FixedDec::from(10u32.pow(chunk.len() as u32)) + FixedDec::from(val);
+        }
+        let pow = pow10(exp10.unsigned_abs());
+        if exp10 >= 0 {
+            res *= pow;
+        } else {
+            let bits = (sig_digits as f64 * LOG2_10).ceil() as usize;
+            res = res.div_prec(&pow, bits.div_ceil(32) + 1);
+        }
+        if sign == NEG {
+            res.negate();
+        }
+        Ok(res)
+    }
+}
+
+fn pow10(exp: u32) -> FixedDec {
+    let mut res = FixedDec::one();
+    let mut base = FixedDec::from(10u32);
+    let mut exp = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res *= &base;
+        }
+        base = &base * &base;
+        exp >>= 1;
+    }
+    res
+}
+
+impl FixedDec {
+    // every binary fraction has a finite decimal expansion, so this is exact
+    fn decimal_digits(&self) -> (String, String) {
+        let mut whole: Vec<u32> = (0..self.dec.max(0)).map(|i| self.part(i)).collect();
+        let mut chunks = Vec::new();
+        while whole.iter().any(|&p| p != 0) {
+            let mut rem = 0u64;
+            for p in &mut whole {
+                let cur = (rem << 32) | *p as u64;
+                *p = (cur / CHUNK as u64) as u32;
+                rem = cur % CHUNK as u64;
+            }
+            chunks.push(rem as u32);
+        }
+        let mut whole_str = match chunks.pop() {
+            Some(first) => first.to_string(),
+            None => "0".to_string(),
+        };
+        for chunk in chunks.iter().rev() {
+            whole_str += &format!("{:09}", chunk);
+        }
+
+        let mut frac: Vec<u32> = (self.dec..self.parts.len() as i32)
+            .map(|i| self.part(i))
+            .collect();
+        let mut frac_str = String::new();
+        while frac.iter().any(|&p| p != 0) {
+            let mut carry = 0u64;
+            for p in frac.iter_mut().rev() {
+                let cur = *p as u64 * CHUNK as u64 + carry;
+                *p = cur as u32;
+                carry = cur >> 32;
+            }
+            frac_str += &format!("{:09}", carry);
+        }
+        let trimmed = frac_str.trim_end_matches('0').len();
+        frac_str.truncate(trimmed);
+        (whole_str, frac_str)
+    }
+}
+
+impl Display for FixedDec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (whole, mut frac) = self.decimal_digits();
+        let mut digits = whole.into_bytes();
+        let Some(prec) = f.precision() else {
+            let mut res = String::from_utf8(digits).unwrap();
+            if !frac.is_empty() {
+                res += ".";
+                res += &frac;
+            }
+            return f.pad_integral(self.is_pos(), "", &res);
+        };
+        let round_up = match frac.as_bytes().get(prec) {
+            Some(b'0'..=b'4') | None => false,
+            Some(b'5') if frac.len() == prec + 1 => {
+                // exactly halfway, so round to even
+                let last = if prec == 0 {
+                    digits.last()
+                } else {
+                    frac.as_bytes().get(prec - 1)
+                };
+                last.is_some_and(|d| (d - b'0') % 2 == 1)
+            }
+            Some(_) => true,
+        };
+        frac.truncate(prec);
+        frac.extend((frac.len()..prec).map(|_| '0'));
+        digits.extend(frac.into_bytes());
+        if round_up {
+            let mut i = digits.len();
+            loop {
+                if i == 0 {
+                    digits.insert(0, b'1');
+                    break;
+                }
+                i -= 1;
+                if digits[i] == b'9' {
+                    digits[i] = b'0';
+                } else {
+                    digits[i] += 1;
+                    break;
+                }
+            }
+        }
+        let split = digits.len() - prec;
+        let mut res = String::from_utf8(digits[..split].to_vec()).unwrap();
+        if prec > 0 {
+            res += ".";
+            res += std::str::from_utf8(&digits[split..]).unwrap();
+        }
+        f.pad_integral(self.is_pos(), "", &res)
+    }
+}
+
+impl FixedDec {
+    // the parts written out in hex, which is exact and a lot shorter than
+    // the decimal expansion of a deep position
+    pub fn to_hex(&self) -> String {
+        let whole: String = (0..self.dec.max(0))
+            .map(|i| format!("{:08x}", self.part(i)))
+            .collect();
+        let whole = whole.trim_start_matches('0');
+        let frac: String = (self.dec..self.parts.len() as i32)
+            .map(|i| format!("{:08x}", self.part(i)))
+            .collect();
+        let frac = frac.trim_end_matches('0');
+        let mut res = String::new();
+        if self.is_neg() {
+            res += "-";
+        }
+        res += if whole.is_empty() { "0" } else { whole };
+        if !frac.is_empty() {
+            res += ".";
+            res += frac;
+        }
+        res
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, ParseFixedDecError> {
+        let (sign, s) = match s.as_bytes().first() {
+            Some(b'-') => (NEG, &s[1..]),
+            _ => (POS, s),
+        };
+        let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+        if whole.is_empty() && frac.is_empty() {
+            return Err(ParseFixedDecError::Empty);
+        }
+        if !whole
+            .bytes()
+            .chain(frac.bytes())
+            .all(|b| b.is_ascii_hexdigit())
+        {
+            return Err(ParseFixedDecError::InvalidDigit);
+        }
+        let chunk = |digits: &str| u32::from_str_radix(digits, 16).unwrap();
+        // pad to whole parts on the outside of the point
+        let whole = format!("{}{}", "0".repeat((8 - whole.len() % 8) % 8), whole);
+        let frac = format!("{}{}", frac, "0".repeat((8 - frac.len() % 8) % 8));
+        let mut res = FixedDec {
+            sign,
+            dec: (whole.len() / 8) as i32,
+            parts: (0..whole.len() / 8)
+                .map(|i| chunk(&whole[i * 8..i * 8 + 8]))
+                .chain((0..frac.len() / 8).map(|i| chunk(&frac[i * 8..i * 8 + 8])))
+                .collect(),
+        };
+        res.trim();
+        Ok(res)
+    }
+}
@@ -151,3 +151,443 @@ fn ord() {
     test(0.0, 0.0);
     test(1.5000, -33.0);
 }
+
+#[test]
+fn div() {
+    fn test(x: f32, y: f32) {
+        let a = x / y;
+        let dec = FixedDec::from(x) / FixedDec::from(y);
+        assert_eq_f32!(a, f32::from(&dec), dec, "{:?} / {:?}", x, y);
+    }
+    test(0.0, 1.0);
+    test(1.0, 1.0);
+    test(1.0, 2.0);
+    test(2.0, 1.0);
+    test(1.0, 3.0);
+    test(-1.0, 3.0);
+    test(1.0, -3.0);
+    test(-1.0, -3.0);
+    test(1000.0, 0.125);
+    test(0.03819, 0.0183488);
+    test(30492.39, 9130.391);
+    test(-249.0, 1234.5678);
+    test(0.000031421, 0.0042042);
+    test(1000000000.75, 1000000000.0);
+}
+
+#[test]
+fn recip() {
+    fn test(x: f32) {
+        let a = 1.0 / x;
+        let dec = FixedDec::from(x).recip(4);
+        assert_eq_f32!(a, f32::from(&dec), dec, "1 / {:?}", x);
+    }
+    test(1.0);
+    test(2.0);
+    test(3.0);
+    test(-3.0);
+    test(0.75);
+    test(1.75);
+    test(9130.391);
+    test(-30492.39);
+    test(1000000000.75);
+    let x = FixedDec::from(1) >> 100;
+    let mut expected = FixedDec::from(1) << 100;
+    expected.trim();
+    assert_eq!(x.recip(2), expected);
+    let x = FixedDec::from(3);
+    let one = &x * &x.recip(8);
+    assert!(one < FixedDec::one());
+    assert!(one.parts()[..7].iter().all(|&p| p == u32::MAX), "{:b}", one);
+}
+
+#[test]
+fn from_str() {
+    fn test(s: &str) {
+        let x: f32 = s.parse().unwrap();
+        let dec: FixedDec = s.parse().unwrap();
+        assert_eq_f32!(x, f32::from(&dec), dec, "{:?}", s);
+    }
+    test("0");
+    test("-0");
+    test("1");
+    test("-1");
+    test("+1.5");
+    test("0.75");
+    test(".125");
+    test("3.");
+    test("-3.75");
+    test("1000000000.75");
+    test("4.39");
+    test("0.0000310");
+    test("-0.743643887037158704752191506114774");
+    test("0.131825904205311970493132056385139");
+    test("1e3");
+    test("1.5E-3");
+    test("-2.5e+2");
+    test("12345678901234567890e-19");
+    assert!("".parse::<FixedDec>().is_err());
+    assert!("-".parse::<FixedDec>().is_err());
+    assert!(".".parse::<FixedDec>().is_err());
+    assert!("1.2.3".parse::<FixedDec>().is_err());
+    assert!("1e".parse::<FixedDec>().is_err());
+    assert!("0x10".parse::<FixedDec>().is_err());
+    assert_eq!(
+        "1e2000000000".parse::<FixedDec>(),
+        Err(string::ParseFixedDecError::ExponentTooLarge)
+    );
+    assert_eq!(
+        "1e-2000000000".parse::<FixedDec>(),
+        Err(string::ParseFixedDecError::ExponentTooLarge)
+    );
+    assert!("1e-9000".parse::<FixedDec>().is_ok());
+    // only where the number starts counts, not how many digits it has
+    let long = format!("-1.{}", "7".repeat(20_000));
+    let parsed: FixedDec = long.parse().unwrap();
+    assert!((f64::from(parsed) + 1.0 + 7.0 / 9.0).abs() < 1e-15);
+    let tiny = format!("0.{}1", "0".repeat(10_000));
+    assert_eq!(
+        tiny.parse::<FixedDec>(),
+        Err(string::ParseFixedDecError::ExponentTooLarge)
+    );
+    let small = format!("0.{}1", "0".repeat(9_998));
+    assert!(small.parse::<FixedDec>().is_ok());
+    assert!("0.00e99999".parse::<FixedDec>().unwrap().is_zero());
+}
+
+#[test]
+fn display() {
+    fn test(x: f32, s: &str) {
+        assert_eq!(format!("{}", FixedDec::from(x)), s);
+    }
+    test(0.0, "0");
+    test(1.0, "1");
+    test(-1.0, "-1");
+    test(0.75, "0.75");
+    test(-3.75, "-3.75");
+    test(1.0 / 1024.0, "0.0009765625");
+    test(4294967296.0, "4294967296");
+    test(1e20, "100000002004087734272");
+    let x = FixedDec::from(1) >> 100;
+    assert_eq!(x.to_string(), "0.0000000000000000000000000000007888609052210118054117285652827862296732064351090230047702789306640625");
+
+    fn test_prec(x: f32, prec: usize, s: &str) {
+        assert_eq!(format!("{:.*}", prec, FixedDec::from(x)), s);
+        assert_eq!(format!("{:.*}", prec, x), s);
+    }
+    test_prec(0.0, 2, "0.00");
+    test_prec(0.75, 0, "1");
+    test_prec(0.5, 0, "0");
+    test_prec(1.5, 0, "2");
+    test_prec(0.125, 2, "0.12");
+    test_prec(0.375, 2, "0.38");
+    test_prec(-3.75, 1, "-3.8");
+    test_prec(9.96875, 1, "10.0");
+    test_prec(-0.03125, 1, "-0.0");
+    assert_eq!(format!("{:>8.2}", FixedDec::from(-1.5)), "   -1.50");
+    assert_eq!(format!("{:+}", FixedDec::from(1.5)), "+1.5");
+
+    let s = "-0.743643887037158704752191506114774";
+    let dec: FixedDec = s.parse().unwrap();
+    assert_eq!(format!("{:.33}", dec), s);
+    assert_eq!(dec.to_string().parse::<FixedDec>().unwrap(), dec);
+}
+
+#[test]
+fn sqrt() {
+    fn test(x: f32) {
+        let a = x.sqrt();
+        let dec = FixedDec::from(x).sqrt(3);
+        assert_eq_f32!(a, f32::from(&dec), dec, "sqrt({:?})", x);
+    }
+    test(0.0);
+    test(1.0);
+    test(2.0);
+    test(4.0);
+    test(0.25);
+    test(0.5);
+    test(3.75);
+    test(0.0000310);
+    test(0.03819);
+    test(49.0);
+    test(f32::from_bits(0b00010000_01000000_00000000_00000001));
+    assert_eq!(FixedDec::from(4).sqrt(4), FixedDec::from(2));
+    assert_eq!(FixedDec::from(0.0625).sqrt(1), FixedDec::from(0.25));
+
+    // the result should be the exact root truncated to the precision
+    let x = FixedDec::from(2);
+    let root = x.sqrt(6);
+    let ulp = FixedDec::from(1) >> (32 * root.dec_len());
+    assert!((&x - &(&root * &root)).is_pos());
+    let next = &root + &ulp;
+    let over = &next * &next - x;
+    assert!(over.is_pos() && !over.is_zero());
+}
+
+#[test]
+fn powi() {
+    fn test(x: f32, n: u32) {
+        let a = x.powi(n as i32);
+        let dec = FixedDec::from(x).powi(n);
+        assert_eq_f32!(a, f32::from(&dec), dec, "{:?}^{:?}", x, n);
+    }
+    test(0.0, 0);
+    test(0.0, 2);
+    test(2.0, 0);
+    test(2.0, 1);
+    test(2.0, 10);
+    test(-2.0, 3);
+    test(-2.0, 4);
+    test(0.5, 7);
+    test(1.20904, 5);
+    test(-0.743643, 8);
+    test(0.03819, 3);
+    assert_eq!(
+        FixedDec::from(3).powi(40),
+        FixedDec::from(3).powi(20).powi(2)
+    );
+}
+
+#[test]
+fn conversion_f64() {
+    fn test(x: f64) {
+        let dec = FixedDec::from(x);
+        let res = f64::from(&dec);
+        assert!(
+            x == res,
+            "\n  expect: {:?}\n found: {:?}\n  from: {:?}",
+            x,
+            res,
+            dec
+        );
+    }
+    test(0.0);
+    test(-0.0);
+    test(1.0);
+    test(-1.0);
+    test(0.1);
+    test(-3.75);
+    test(1e300);
+    test(1e-300);
+    test(f64::MAX);
+    test(f64::MIN_POSITIVE);
+    test(f64::from_bits(1));
+    test(f64::from_bits(0x000fffffffffffff));
+    test(-0.7436438870371587);
+
+    // ties round to even, including when it carries into the exponent
+    let half_ulp = FixedDec::from(1) >> 53;
+    assert_eq!(f64::from(FixedDec::one() + half_ulp.clone()), 1.0);
+    let three_halves = FixedDec::from(3) >> 53;
+    assert_eq!(
+        f64::from(FixedDec::one() + three_halves),
+        1.0 + 2f64.powi(-51)
+    );
+    let sticky = &half_ulp + &(FixedDec::from(1) >> 200);
+    assert_eq!(f64::from(FixedDec::one() + sticky), 1.0 + f64::EPSILON);
+    assert_eq!(f64::from(FixedDec::from(u64::MAX)), 2f64.powi(64));
+
+    // subnormals
+    assert_eq!(f64::from(FixedDec::from(1) >> 1075), 0.0);
+    assert_eq!(f64::from(FixedDec::from(3) >> 1076), f64::from_bits(1));
+    assert_eq!(f64::from(FixedDec::from(3) >> 1075), f64::from_bits(2));
+    assert_eq!(f64::from(FixedDec::from(1) >> 2000), 0.0);
+    assert_eq!(f64::from(FixedDec::from(1) << 1024), f64::INFINITY);
+    assert_eq!(f64::from(-(FixedDec::from(1) << 1024)), f64::NEG_INFINITY);
+}
+
+// in release builds too, since there's no FixedDec it could turn into
+#[test]
+#[should_panic(expected = "cannot convert NaN to FixedDec")]
+fn conversion_f64_nan() {
+    let _ = FixedDec::from(f64::NAN);
+}
+
+#[test]
+#[should_panic(expected = "cannot convert -inf to FixedDec")]
+fn conversion_f64_inf() {
+    let _ = FixedDec::from(f64::NEG_INFINITY);
+}
+
+#[test]
+fn conversion_int() {
+    fn test(dec: FixedDec, s: &str) {
+        assert_eq!(dec.to_string(), s);
+    }
+    test(FixedDec::from(0u64), "0");
+    test(FixedDec::from(u64::MAX), "18446744073709551615");
+    test(FixedDec::from(i64::MIN), "-9223372036854775808");
+    test(FixedDec::from(-1i64), "-1");
+    test(
+        FixedDec::from(u128::MAX),
+        "340282366920938463463374607431768211455",
+    );
+    test(
+        FixedDec::from(1u128 << 100),
+        "1267650600228229401496703205376",
+    );
+}
+
+#[test]
+fn log2() {
+    fn test(x: FixedDec, expect: f64) {
+        let res = x.log2();
+        assert!(
+            (res - expect).abs() < 1e-9,
+            "log2({:?}) = {} != {}",
+            x,
+            res,
+            expect
+        );
+    }
+    test(FixedDec::from(1), 0.0);
+    test(FixedDec::from(-8), 3.0);
+    test(FixedDec::from(0.75), 0.75f64.log2());
+    test(FixedDec::from(3) >> 5000, 3f64.log2() - 5000.0);
+    test(FixedDec::from(5) << 2000, 5f64.log2() + 2000.0);
+    assert_eq!(FixedDec::zero().log2(), f64::NEG_INFINITY);
+}
+
+#[test]
+fn hex() {
+    fn test(x: FixedDec, s: &str) {
+        assert_eq!(x.to_hex(), s);
+        let back = FixedDec::from_hex(s).unwrap();
+        assert!((&back - &x).is_zero(), "{:?} != {:?}", back, x);
+    }
+    test(FixedDec::zero(), "0");
+    test(FixedDec::from(1), "1");
+    test(FixedDec::from(-0.5), "-0.8");
+    test(FixedDec::from(1u64 << 40), "10000000000");
+    test(FixedDec::from(1) >> 40, "0.0000000001");
+    test(FixedDec::from(-3.75), "-3.c");
+    assert_eq!(FixedDec::from_hex("-0.0"), Ok(FixedDec::zero()));
+    assert_eq!(
+        FixedDec::from_hex("."),
+        Err(string::ParseFixedDecError::Empty)
+    );
+    assert_eq!(
+        FixedDec::from_hex("0.g"),
+        Err(string::ParseFixedDecError::InvalidDigit)
+    );
+}
+
+proptest::proptest! {
+    #[test]
+    fn f32_matches_f64(bits in proptest::num::u32::ANY) {
+        let x = f32::from_bits(bits);
+        proptest::prop_assume!(x.is_finite());
+        let a = FixedDec::from(x);
+        let b = FixedDec::from(x as f64);
+        proptest::prop_assert!((&a - &b).is_zero(), "{:?} != {:?}", a, b);
+        // f32 conversion truncates, the f64 one rounds
+        let res = f32::from(&a);
+        let expect = f64::from(&a) as f32;
+        proptest::prop_assert!(
+            (res - expect).abs() <= f32::from_bits(expect.abs().to_bits() + 1) - expect.abs(),
+            "{:?} vs {:?} from {:?}",
+            res,
+            expect,
+            a
+        );
+    }
+
+    #[test]
+    fn f64_round_trip(bits in proptest::num::u64::ANY) {
+        let x = f64::from_bits(bits);
+        proptest::prop_assume!(x.is_finite());
+        proptest::prop_assert_eq!(f64::from(FixedDec::from(x)), x);
+    }
+
+    #[test]
+    fn f64_rounding(x in proptest::num::u128::ANY, sh in 0i32..1200) {
+        // integers as f64 are correctly rounded, so use those to check
+        let expect = x as f64 * 2f64.powi(-sh.min(1000)) * 2f64.powi(-(sh - sh.min(1000)));
+        proptest::prop_assume!(expect == 0.0 || expect.is_normal());
+        proptest::prop_assert_eq!(f64::from(FixedDec::from(x) >> sh), expect);
+    }
+
+    #[test]
+    fn wgsl_from_f32(bits in proptest::num::u32::ANY) {
+        let x = f32::from_bits(bits);
+        proptest::prop_assume!(x == 0.0 || x.is_normal());
+        let dec = wgsl::from_f32(x, 4);
+        proptest::prop_assert!((&dec - &FixedDec::from(x)).is_zero(), "{:?} from {:?}", dec, x);
+    }
+
+    #[test]
+    fn wgsl_to_f32(bits in proptest::num::u32::ANY) {
+        let x = f32::from_bits(bits);
+        proptest::prop_assume!(x == 0.0 || (x.is_normal() && x.abs() > 1e-30));
+        proptest::prop_assert_eq!(wgsl::to_f32(&wgsl::from_f32(x, 4)), x);
+    }
+
+    // the shader's ops only drop parts past len, so values that fit have to
+    // come out exact
+    #[test]
+    fn wgsl_matches_exact(x in -(1i32 << 20)..(1 << 20), y in -(1i32 << 20)..(1 << 20)) {
+        let (x, y) = (x as f32 / 1024.0, y as f32 / 1024.0);
+        let (a, b) = (wgsl::from_f32(x, 4), wgsl::from_f32(y, 4));
+        let prod = wgsl::mul(&a, &b);
+        let expect = FixedDec::from(x) * FixedDec::from(y);
+        proptest::prop_assert!((&prod - &expect).is_zero(), "{:?} * {:?} = {:?}", x, y, prod);
+
+        let root = wgsl::sqrt(&wgsl::mul(&a, &a));
+        let expect = FixedDec::from(x.abs());
+        proptest::prop_assert!((&root - &expect).is_zero(), "sqrt({:?}^2) = {:?}", x, root);
+
+        // subtracting is off by one in the last part, like in op.rs
+        let (a, b) = (wgsl::from_f32(x.abs(), 4), wgsl::from_f32(y.abs(), 4));
+        let cube = wgsl::pow(&wgsl::from_f32(x / 8.0, 4), 3);
+        let expect = FixedDec::from(x / 8.0).powi(3);
+        proptest::prop_assert!((&cube - &expect).is_zero(), "{:?}^3 = {:?}", x / 8.0, cube);
+
+        let sum = wgsl::add(&a, &b);
+        let expect = FixedDec::from(x.abs() + y.abs());
+        proptest::prop_assert!((&sum - &expect).is_zero(), "{:?} + {:?} = {:?}", x, y, sum);
+    }
+
+    // sqrt is the exact root truncated on both sides, so it matches bit for
+    // bit; pow truncates every product, so it matches powi doing the same
+    #[test]
+    fn wgsl_sqrt_pow(
+        parts in proptest::collection::vec(proptest::num::u32::ANY, 4),
+        dec in -3i32..4,
+        n in 0u32..12,
+    ) {
+        let len = parts.len();
+        let x = FixedDec::from_parts(POS, dec, parts);
+        let root = wgsl::sqrt(&x);
+        let expect = x.sqrt(len);
+        proptest::prop_assert!((&root - &expect).is_zero(), "sqrt({:?}) = {:?}", x, root);
+
+        let truncate = |mut x: FixedDec| {
+            x.trim();
+            x.set_precision(len);
+            x
+        };
+        let mut expect = FixedDec::one();
+        let mut base = x.clone();
+        let mut exp = n;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                expect = truncate(&expect * &base);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = truncate(&base * &base);
+            }
+        }
+        let res = wgsl::pow(&x, n);
+        proptest::prop_assert!((&res - &expect).is_zero(), "{:?}^{:?} = {:?}", x, n, res);
+    }
+
+    #[test]
+    fn hex_round_trip(bits in proptest::num::u64::ANY, sh in -100i32..100) {
+        let x = f64::from_bits(bits);
+        proptest::prop_assume!(x.is_finite());
+        let dec = FixedDec::from(x) >> sh;
+        let back = FixedDec::from_hex(&dec.to_hex()).unwrap();
+        proptest::prop_assert!((&back - &dec).is_zero(), "{:?} != {:?}", back, dec);
+    }
+}
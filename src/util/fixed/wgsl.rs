@@ -0,0 +1,282 @@
+use super::{FixedDec, POS};
+
+// the fixed point functions from compute/fixed.wgsl, step for step, so the
+// cpu renderer rounds and overflows the same way the shader does. every
+// FixedDec going in has to have len parts, and the results keep len parts
+// instead of growing and trimming like the ops in op.rs
+
+const FRAC_BIT: u32 = 1 << 23;
+const FRAC_MASK: u32 = FRAC_BIT - 1;
+
+pub fn zero(len: usize) -> FixedDec {
+    FixedDec {
+        sign: POS,
+        dec: 0,
+        parts: vec![0; len],
+    }
+}
+
+pub fn one(len: usize) -> FixedDec {
+    let mut res = zero(len);
+    res.parts[0] = 1;
+    res.dec = 1;
+    res
+}
+
+pub fn to_f32(value: &FixedDec) -> f32 {
+    let parts = &value.parts;
+    let sign = (value.sign as u32) << 31;
+    let Some(skip_count) = parts.iter().position(|p| *p != 0) else {
+        return if value.sign { -0.0 } else { 0.0 };
+    };
+    let v = parts[skip_count];
+    let mut start = v.leading_zeros() + 1;
+    let exp_i = (value.dec - skip_count as i32) * 32 - start as i32;
+    let mut frac_sh = 0;
+    let mut exp = 0;
+    if exp_i >= -127 {
+        if exp_i == -127 {
+            start -= 1;
+        }
+        exp = (exp_i + 127) as u32;
+    } else {
+        frac_sh = (-(exp_i + 32 * 4 - 1)) as u32;
+        if frac_sh < 23 {
+            start -= 1;
+        } else {
+            return 0.0;
+        }
+    }
+    let mut frac = if start > 9 {
+        let sh = start - 9;
+        let v2 = parts.get(skip_count + 1).map_or(0, |p| p >> (32 - sh));
+        v.wrapping_shl(sh).wrapping_add(v2)
+    } else {
+        v >> (9 - start)
+    };
+    frac &= !(1 << 23);
+    f32::from_bits((frac >> frac_sh).wrapping_add(exp << 23).wrapping_add(sign))
+}
+
+pub fn from_f32(value: f32, len: usize) -> FixedDec {
+    let raw = value.to_bits() & !(1 << 31);
+    let mut exp = (raw >> 23) as i32 - 127;
+    let mut frac = raw & FRAC_MASK;
+    let mut start = -exp;
+    if exp == -127 {
+        exp = -126;
+        start = -exp;
+    } else {
+        frac += FRAC_BIT;
+        start -= 1;
+    }
+    let end = -exp + 23;
+    let start_i = start.div_euclid(32);
+    let end_i = (end - 1).div_euclid(32);
+    let mut parts = vec![0; len];
+    let mut dec = -start_i;
+    if start_i == end_i {
+        parts[0] = frac.wrapping_shl((8 - start.rem_euclid(32)) as u32);
+    } else {
+        let s = end.rem_euclid(32) as u32;
+        let val_high = frac.wrapping_shr(s);
+        let val_low = frac.wrapping_shl(32 - s);
+        let mut i = 0;
+        if val_high != 0 {
+            parts[0] = val_high;
+            i += 1;
+        } else {
+            dec -= 1;
+        }
+        if val_low != 0 {
+            parts[i] = val_low;
+        }
+    }
+    if parts[0] == 0 && parts[1] == 0 {
+        dec = 0;
+    }
+    FixedDec {
+        sign: value < 0.0,
+        dec,
+        parts,
+    }
+}
+
+pub fn add(lhs: &FixedDec, rhs: &FixedDec) -> FixedDec {
+    let len = lhs.parts.len();
+    let mut dest = FixedDec {
+        sign: lhs.sign,
+        dec: lhs.dec.max(rhs.dec),
+        parts: vec![0; len],
+    };
+    let rhs_offset = rhs.dec - dest.dec;
+    let lhs_offset = lhs.dec - dest.dec;
+    let mut carry = false;
+    if lhs.sign == rhs.sign {
+        for i in (0..len).rev() {
+            let a = lhs.part(i as i32 + lhs_offset);
+            let b = rhs.part(i as i32 + rhs_offset);
+            let res = a.wrapping_add(b).wrapping_add(carry as u32);
+            dest.parts[i] = res;
+            carry = res < a;
+        }
+        if carry {
+            dest.parts.rotate_right(1);
+            dest.parts[0] = 1;
+            dest.dec += 1;
+        }
+    } else {
+        for i in (0..len).rev() {
+            let a = lhs.part(i as i32 + lhs_offset);
+            let b = rhs.part(i as i32 + rhs_offset);
+            let res = a.wrapping_sub(b).wrapping_sub(carry as u32);
+            dest.parts[i] = res;
+            carry = a < res;
+        }
+        if carry {
+            dest.sign = !dest.sign;
+            for part in &mut dest.parts {
+                *part = !*part;
+            }
+        }
+    }
+    dest
+}
+
+pub fn sub(lhs: &FixedDec, rhs: &FixedDec) -> FixedDec {
+    let mut r = rhs.clone();
+    r.sign = !r.sign;
+    add(lhs, &r)
+}
+
+pub fn mul(lhs: &FixedDec, rhs: &FixedDec) -> FixedDec {
+    let len = lhs.parts.len();
+    let mut parts = vec![0u32; len * 2];
+    let mut dec = lhs.dec + rhs.dec;
+    for i in (0..len).rev() {
+        let x = lhs.parts[i];
+        let mut carry = 0u32;
+        for j in (0..len).rev() {
+            let y = rhs.parts[j];
+
+            // the shader's widening mul, done in 16 bit halves
+            let lsb = x.wrapping_mul(y);
+            let (a, b) = (x & 0xffff, x >> 16);
+            let (c, d) = (y & 0xffff, y >> 16);
+            let ad = a * d + ((a * c) >> 16);
+            let bc = b * c;
+            let car = ad > (u32::MAX - bc);
+            let msb = (ad.wrapping_add(bc) >> 16) + ((car as u32) << 16) + b * d;
+
+            let k = i + j + 1;
+            let res = parts[k].wrapping_add(lsb);
+            let carry1 = res < lsb;
+            let res2 = res.wrapping_add(carry);
+            let carry2 = res2 < res;
+            parts[k] = res2;
+            carry = (carry1 as u32)
+                .wrapping_add(carry2 as u32)
+                .wrapping_add(msb);
+        }
+        parts[i] = carry;
+    }
+
+    let skip = parts.iter().take_while(|&&p| p == 0).count();
+    dec -= skip as i32;
+    let mut new_parts = vec![0; len];
+    for (new, &part) in new_parts.iter_mut().zip(&parts[skip..]) {
+        *new = part;
+    }
+    FixedDec {
+        sign: lhs.sign != rhs.sign,
+        dec,
+        parts: new_parts,
+    }
+}
+
+pub fn sqrt(x: &FixedDec) -> FixedDec {
+    let len = x.parts.len();
+    let Some(start) = x.parts.iter().position(|&p| p != 0) else {
+        return zero(len);
+    };
+    let mut dec = x.dec - start as i32;
+    let mut rad = vec![0u32; len * 2];
+    let mut k = 0;
+    if dec.rem_euclid(2) == 1 {
+        k = 1;
+        dec += 1;
+    }
+    for &part in &x.parts[start..] {
+        rad[k] = part;
+        k += 1;
+    }
+
+    let mut root = vec![0u32; len];
+    let mut rem = vec![0u32; len + 1];
+    for i in 0..len * 32 {
+        let bits = (rad[i / 16] >> (30 - (i % 16) * 2)) & 3;
+        let mut carry = bits;
+        for r in rem.iter_mut().rev() {
+            let next = *r >> 30;
+            *r = (*r << 2) | carry;
+            carry = next;
+        }
+        let mut trial = vec![0u32; len + 1];
+        carry = 1;
+        for j in (0..len).rev() {
+            trial[j + 1] = (root[j] << 2) | carry;
+            carry = root[j] >> 30;
+        }
+        trial[0] = carry;
+        let ge = rem >= trial;
+        if ge {
+            let mut borrow = false;
+            for j in (0..=len).rev() {
+                let a = rem[j];
+                rem[j] = a.wrapping_sub(trial[j]).wrapping_sub(borrow as u32);
+                borrow = a < trial[j] || (a == trial[j] && borrow);
+            }
+        }
+        carry = ge as u32;
+        for r in root.iter_mut().rev() {
+            let next = *r >> 31;
+            *r = (*r << 1) | carry;
+            carry = next;
+        }
+    }
+    FixedDec {
+        sign: POS,
+        dec: dec / 2,
+        parts: root,
+    }
+}
+
+// FixedDec::powi, but every mul is truncated to len parts like mul is
+pub fn pow(x: &FixedDec, n: u32) -> FixedDec {
+    let mut res = one(x.parts.len());
+    let mut base = x.clone();
+    let mut exp = n;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = mul(&res, &base);
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = mul(&base, &base);
+        }
+    }
+    res
+}
+
+// only looks at the first part, and not at the signs
+pub fn gt(x: &FixedDec, y: &FixedDec) -> bool {
+    if x.dec != y.dec {
+        return x.dec > y.dec;
+    }
+    x.parts[0] > y.parts[0]
+}
+
+// x.sign = neg in the shader, which unlike negate doesn't care about zero
+pub fn set_sign(x: &mut FixedDec, neg: bool) {
+    x.sign = neg;
+}
@@ -0,0 +1,46 @@
+use crate::util::FixedDec;
+
+use super::{ldexp, FloatExp};
+
+impl From<f64> for FloatExp {
+    fn from(value: f64) -> Self {
+        Self::new(value, 0)
+    }
+}
+
+impl From<f32> for FloatExp {
+    fn from(value: f32) -> Self {
+        Self::new(value as f64, 0)
+    }
+}
+
+impl From<FloatExp> for f64 {
+    fn from(value: FloatExp) -> Self {
+        ldexp(value.mant, value.exp)
+    }
+}
+
+impl From<&FixedDec> for FloatExp {
+    fn from(value: &FixedDec) -> Self {
+        let Some(k) = value.parts().iter().position(|&p| p != 0) else {
+            return Self::ZERO;
+        };
+        let dec = value.parts().len() as i32 - value.dec_len();
+        let lz = value.parts()[k].leading_zeros() as i32;
+        // shift the top bit to just after the point, so f64 can take the rest
+        let exp = 32 * (dec - k as i32) - lz;
+        Self::new(f64::from(value >> exp), exp)
+    }
+}
+
+impl From<FixedDec> for FloatExp {
+    fn from(value: FixedDec) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<FloatExp> for FixedDec {
+    fn from(value: FloatExp) -> Self {
+        FixedDec::from(value.mant) << value.exp
+    }
+}
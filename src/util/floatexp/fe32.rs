@@ -0,0 +1,113 @@
+// the FloatExp functions from compute/floatexp.wgsl, with the f32 mantissa
+// the shader has, so the cpu renderer rounds the same way it does
+
+// past this difference in exponents the smaller value can't change the f32 sum
+const MAX_EXP_DIFF: i32 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Fe32 {
+    pub mant: f32,
+    pub exp: i32,
+}
+
+pub const ZERO: Fe32 = Fe32 { mant: 0.0, exp: 0 };
+pub const ONE: Fe32 = Fe32 { mant: 0.5, exp: 1 };
+
+pub fn new(mant: f32, exp: i32) -> Fe32 {
+    if mant == 0.0 {
+        return ZERO;
+    }
+    let (mant, e) = frexp(mant);
+    Fe32 { mant, exp: exp + e }
+}
+
+pub fn from_f32(x: f32) -> Fe32 {
+    new(x, 0)
+}
+
+pub fn to_f32(x: Fe32) -> f32 {
+    if x.mant == 0.0 || x.exp < -149 {
+        return 0.0 * x.mant;
+    }
+    if x.exp > 128 {
+        return x.mant.signum() * f32::MAX;
+    }
+    ldexp(x.mant, x.exp)
+}
+
+pub fn add(x: Fe32, y: Fe32) -> Fe32 {
+    if x.mant == 0.0 {
+        return y;
+    }
+    if y.mant == 0.0 {
+        return x;
+    }
+    let diff = x.exp - y.exp;
+    if diff > MAX_EXP_DIFF {
+        x
+    } else if diff < -MAX_EXP_DIFF {
+        y
+    } else if diff >= 0 {
+        new(x.mant + ldexp(y.mant, -diff), x.exp)
+    } else {
+        new(ldexp(x.mant, diff) + y.mant, y.exp)
+    }
+}
+
+pub fn sub(x: Fe32, y: Fe32) -> Fe32 {
+    add(
+        x,
+        Fe32 {
+            mant: -y.mant,
+            exp: y.exp,
+        },
+    )
+}
+
+pub fn mul(x: Fe32, y: Fe32) -> Fe32 {
+    new(x.mant * y.mant, x.exp + y.exp)
+}
+
+pub fn div(x: Fe32, y: Fe32) -> Fe32 {
+    new(x.mant / y.mant, x.exp - y.exp)
+}
+
+pub fn length(x: Fe32, y: Fe32) -> Fe32 {
+    let diff = x.exp - y.exp;
+    if y.mant == 0.0 || diff > MAX_EXP_DIFF {
+        return Fe32 {
+            mant: x.mant.abs(),
+            exp: x.exp,
+        };
+    }
+    if x.mant == 0.0 || diff < -MAX_EXP_DIFF {
+        return Fe32 {
+            mant: y.mant.abs(),
+            exp: y.exp,
+        };
+    }
+    let exp = x.exp.max(y.exp);
+    let (a, b) = (ldexp(x.mant, x.exp - exp), ldexp(y.mant, y.exp - exp));
+    new((a * a + b * b).sqrt(), exp)
+}
+
+// splits x into a mantissa in [0.5, 1) and an exponent
+fn frexp(x: f32) -> (f32, i32) {
+    if x == 0.0 || !x.is_finite() {
+        return (x, 0);
+    }
+    let bits = x.to_bits();
+    let exp = ((bits >> 23) & 0xff) as i32;
+    if exp == 0 {
+        // subnormal, scale it into the normal range first
+        let (mant, e) = frexp(x * 2f32.powi(32));
+        return (mant, e - 32);
+    }
+    let mant = f32::from_bits((bits & !(0xff << 23)) | (126 << 23));
+    (mant, exp - 126)
+}
+
+fn ldexp(x: f32, exp: i32) -> f32 {
+    let half = exp / 2;
+    x * 2f32.powi(half) * 2f32.powi(exp - half)
+}
@@ -0,0 +1,80 @@
+mod conversion;
+pub mod fe32;
+mod op;
+#[cfg(test)]
+mod test;
+
+// float with an extended exponent, value = mant * 2^exp; used where f64 runs
+// out of range (deep zoom deltas) but FixedDec is too slow. mant is kept in
+// [0.5, 1) like frexp, which is also what the wgsl version does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloatExp {
+    mant: f64,
+    exp: i32,
+}
+
+impl FloatExp {
+    pub const ZERO: Self = Self { mant: 0.0, exp: 0 };
+    pub const ONE: Self = Self { mant: 0.5, exp: 1 };
+
+    pub fn new(mant: f64, exp: i32) -> Self {
+        let (mant, e) = frexp(mant);
+        Self {
+            mant,
+            exp: if mant == 0.0 { 0 } else { exp + e },
+        }
+    }
+
+    pub fn mant(&self) -> f64 {
+        self.mant
+    }
+
+    pub fn exp(&self) -> i32 {
+        self.exp
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mant == 0.0
+    }
+
+    pub fn is_neg(&self) -> bool {
+        self.mant < 0.0
+    }
+
+    pub fn abs(self) -> Self {
+        Self {
+            mant: self.mant.abs(),
+            exp: self.exp,
+        }
+    }
+
+    pub fn to_bytes(self, bytes: &mut Vec<u8>) {
+        // the gpu only has f32, which can round the mantissa up to 1
+        let gpu = Self::new(self.mant as f32 as f64, self.exp);
+        bytes.extend((gpu.mant as f32).to_le_bytes());
+        bytes.extend(gpu.exp.to_le_bytes());
+    }
+}
+
+// splits x into a mantissa in [0.5, 1) and an exponent
+fn frexp(x: f64) -> (f64, i32) {
+    if x == 0.0 || !x.is_finite() {
+        return (x, 0);
+    }
+    let bits = x.to_bits();
+    let exp = ((bits >> 52) & 0x7ff) as i32;
+    if exp == 0 {
+        // subnormal, scale it into the normal range first
+        let (mant, e) = frexp(x * 2f64.powi(64));
+        return (mant, e - 64);
+    }
+    let mant = f64::from_bits((bits & !(0x7ff << 52)) | (1022 << 52));
+    (mant, exp - 1022)
+}
+
+// x * 2^exp without overflowing in the middle for large exponents
+fn ldexp(x: f64, exp: i32) -> f64 {
+    let exp = exp.clamp(-2200, 2200);
+    let half = exp / 2;
+    x * 2f64.powi(half) * 2f64.powi(exp - half)
+}
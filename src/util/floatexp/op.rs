@@ -0,0 +1,125 @@
+use std::{
+    cmp::Ordering,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use super::{ldexp, FloatExp};
+
+// past this difference in exponents the smaller value can't change the f64 sum
+const MAX_EXP_DIFF: i32 = 64;
+
+impl Add for FloatExp {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        if self.is_zero() {
+            return rhs;
+        }
+        if rhs.is_zero() {
+            return self;
+        }
+        let diff = self.exp - rhs.exp;
+        if diff > MAX_EXP_DIFF {
+            self
+        } else if diff < -MAX_EXP_DIFF {
+            rhs
+        } else if diff >= 0 {
+            Self::new(self.mant + ldexp(rhs.mant, -diff), self.exp)
+        } else {
+            Self::new(ldexp(self.mant, diff) + rhs.mant, rhs.exp)
+        }
+    }
+}
+
+impl Sub for FloatExp {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for FloatExp {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(self.mant * rhs.mant, self.exp + rhs.exp)
+    }
+}
+
+impl Div for FloatExp {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        if rhs.is_zero() {
+            panic!("attempt to divide by zero");
+        }
+        Self::new(self.mant / rhs.mant, self.exp - rhs.exp)
+    }
+}
+
+impl Neg for FloatExp {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            mant: -self.mant,
+            exp: self.exp,
+        }
+    }
+}
+
+impl AddAssign for FloatExp {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl SubAssign for FloatExp {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl MulAssign for FloatExp {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs
+    }
+}
+
+impl DivAssign for FloatExp {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs
+    }
+}
+
+impl PartialOrd for FloatExp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.is_neg(), other.is_neg()) {
+            (false, true) => return Some(Ordering::Greater),
+            (true, false) => return Some(Ordering::Less),
+            _ => (),
+        }
+        if self.is_zero() || other.is_zero() || self.exp == other.exp {
+            return self.mant.partial_cmp(&other.mant);
+        }
+        let ord = self.exp.cmp(&other.exp);
+        Some(if self.is_neg() { ord.reverse() } else { ord })
+    }
+}
+
+impl FloatExp {
+    pub fn sqr(self) -> Self {
+        self * self
+    }
+
+    pub fn mul_pow2(self, exp: i32) -> Self {
+        if self.is_zero() {
+            return self;
+        }
+        Self {
+            mant: self.mant,
+            exp: self.exp + exp,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use super::*;
+use crate::util::FixedDec;
+use num_traits::Zero;
+
+#[test]
+fn normalize() {
+    fn test(x: f64) {
+        let fe = FloatExp::from(x);
+        assert!(
+            fe.is_zero() || (0.5..1.0).contains(&fe.mant().abs()),
+            "{:?}",
+            fe
+        );
+        assert_eq!(f64::from(fe), x, "{:?}", fe);
+    }
+    test(0.0);
+    test(1.0);
+    test(-1.0);
+    test(0.75);
+    test(3.0);
+    test(-1e300);
+    test(1e-300);
+    test(f64::from_bits(1));
+    test(f64::MAX);
+    assert_eq!(FloatExp::ONE, FloatExp::from(1.0));
+}
+
+#[test]
+fn arithmetic() {
+    fn test(x: f64, y: f64) {
+        let (a, b) = (FloatExp::from(x), FloatExp::from(y));
+        assert_eq!(f64::from(a + b), x + y, "{:?} + {:?}", x, y);
+        assert_eq!(f64::from(a - b), x - y, "{:?} - {:?}", x, y);
+        assert_eq!(f64::from(a * b), x * y, "{:?} * {:?}", x, y);
+        if y != 0.0 {
+            assert_eq!(f64::from(a / b), x / y, "{:?} / {:?}", x, y);
+        }
+        assert_eq!(a < b, x < y, "{:?} < {:?}", x, y);
+        assert_eq!(a > b, x > y, "{:?} > {:?}", x, y);
+    }
+    test(0.0, 0.0);
+    test(1.0, 0.0);
+    test(0.0, -2.0);
+    test(1.0, 1.0);
+    test(1.5, -1.5);
+    test(0.25, 3.0);
+    test(-30492.39, 9130.391);
+    test(1e100, 1e-100);
+    test(1e-100, 3e-100);
+    test(-0.743643887, 0.131825904);
+}
+
+#[test]
+fn extended_range() {
+    let tiny = FloatExp::new(0.75, -5000);
+    let sqr = tiny.sqr();
+    assert_eq!(sqr.exp(), -10000);
+    assert_eq!(sqr.mant(), 0.5625);
+    assert_eq!(f64::from(tiny), 0.0);
+    assert_eq!((sqr / tiny).exp(), tiny.exp());
+    assert_eq!(tiny + FloatExp::ONE, FloatExp::ONE);
+    assert_eq!((tiny + tiny).mul_pow2(-1), tiny);
+    assert!(tiny > sqr && -tiny < -sqr && tiny < FloatExp::ONE);
+}
+
+#[test]
+fn fixed() {
+    fn test(x: f64) {
+        let dec = FixedDec::from(x);
+        let fe = FloatExp::from(&dec);
+        assert_eq!(f64::from(fe), x, "{:?}", fe);
+        assert!((&FixedDec::from(fe) - &dec).is_zero(), "{:?}", fe);
+    }
+    test(0.0);
+    test(1.0);
+    test(-3.75);
+    test(0.1);
+    test(1e-300);
+    test(-1e300);
+    test(4294967296.0);
+
+    // past the range of f64
+    let dec = FixedDec::from(3) >> 5000;
+    let fe = FloatExp::from(&dec);
+    assert_eq!((fe.mant(), fe.exp()), (0.75, -4998));
+    assert!((&FixedDec::from(fe) - &dec).is_zero());
+    let dec = -(FixedDec::from(1) >> 4000) + (FixedDec::from(1) >> 4100);
+    let fe = FloatExp::from(&dec);
+    assert_eq!(fe.exp(), -3999);
+    assert_eq!(fe.mant(), -0.5 + 2f64.powi(-101));
+}
+
+#[test]
+fn fe32() {
+    fn test(x: f32, y: f32) {
+        let (a, b) = (fe32::from_f32(x), fe32::from_f32(y));
+        assert!(
+            a.mant == 0.0 || (0.5..1.0).contains(&a.mant.abs()),
+            "{:?}",
+            a
+        );
+        assert_eq!(fe32::to_f32(a), x);
+        assert_eq!(fe32::to_f32(fe32::add(a, b)), x + y, "{:?} + {:?}", x, y);
+        assert_eq!(fe32::to_f32(fe32::sub(a, b)), x - y, "{:?} - {:?}", x, y);
+        assert_eq!(fe32::to_f32(fe32::mul(a, b)), x * y, "{:?} * {:?}", x, y);
+        if y != 0.0 {
+            assert_eq!(fe32::to_f32(fe32::div(a, b)), x / y, "{:?} / {:?}", x, y);
+        }
+        let len = fe32::to_f32(fe32::length(a, b));
+        assert!(
+            (len - x.hypot(y)).abs() <= x.hypot(y) * 1e-6,
+            "|{:?}, {:?}|",
+            x,
+            y
+        );
+    }
+    test(0.0, 0.0);
+    test(1.0, 0.0);
+    test(0.0, -2.0);
+    test(3.0, 4.0);
+    test(1.5, -1.5);
+    test(-30492.39, 9130.391);
+    test(1e-30, 3e-30);
+    test(-0.743_643_9, 0.131_825_9);
+    test(f32::from_bits(1), 0.0);
+
+    // past the range of f32, like a derivative gets
+    let big = fe32::new(0.75, 1000);
+    assert_eq!(fe32::to_f32(big), f32::MAX);
+    assert_eq!(fe32::mul(big, big), fe32::new(0.5625, 2000));
+    let len = fe32::length(big, fe32::new(-0.75, 1000));
+    assert_eq!(len.exp, 1001);
+    assert!((len.mant - 0.75 * std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    assert_eq!(fe32::to_f32(fe32::div(big, big)), 1.0);
+    assert_eq!(fe32::length(big, fe32::ONE), big);
+}
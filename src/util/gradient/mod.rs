@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod test;
+
+// a looping gradient of linear rgb colors, with stops from 0 up to 1 where
+// it wraps back around to the first one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    // sorted by pos, at least one
+    stops: Vec<Stop>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stop {
+    pub pos: f32,
+    pub color: [f32; 3],
+}
+
+// where the stops in a .ugr go up to
+const UGR_LEN: f32 = 400.0;
+
+impl Gradient {
+    pub fn new(mut stops: Vec<Stop>) -> Result<Self, String> {
+        if stops.is_empty() {
+            return Err("no colors".to_string());
+        }
+        if let Some(stop) = stops.iter().find(|s| !(0.0..1.0).contains(&s.pos)) {
+            return Err(format!("position {} isn't in 0 to 1", stop.pos));
+        }
+        stops.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        Ok(Self { stops })
+    }
+
+    // red to green to blue and back, what the shader used to do
+    pub fn hue() -> Self {
+        // the old shader's pi
+        #[allow(clippy::approx_constant)]
+        let pi = 3.1415f32;
+        let stops = (0..256)
+            .map(|i| {
+                let hue = i as f32 / 256.0 * 2.0 * pi;
+                let c = |shift: f32| (hue - shift * pi / 3.0).cos().clamp(0.0, 1.0);
+                Stop {
+                    pos: i as f32 / 256.0,
+                    color: [c(0.0), c(2.0), c(4.0)],
+                }
+            })
+            .collect();
+        Self { stops }
+    }
+
+    // srgb bytes at positions
+    pub fn from_srgb(stops: &[(f32, [u8; 3])]) -> Result<Self, String> {
+        let stops = stops
+            .iter()
+            .map(|(pos, c)| Stop {
+                pos: *pos,
+                color: c.map(linear),
+            })
+            .collect();
+        Self::new(stops)
+    }
+
+    // a fractint .map, a line of `r g b` from 0 to 255 for each color spaced
+    // evenly; anything after the third number is a comment
+    pub fn parse_map(source: &str) -> Result<Self, String> {
+        let mut colors = Vec::new();
+        for (i, line) in source.lines().enumerate() {
+            let mut nums = line.split_whitespace();
+            let Some(first) = nums.next() else {
+                continue;
+            };
+            let parse = |s: Option<&str>| {
+                s.and_then(|s| s.parse::<u8>().ok())
+                    .ok_or_else(|| format!("line {}: expected r g b from 0 to 255", i + 1))
+            };
+            colors.push([
+                parse(Some(first))?,
+                parse(nums.next())?,
+                parse(nums.next())?,
+            ]);
+        }
+        let len = colors.len() as f32;
+        let stops: Vec<_> = colors
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| (i as f32 / len, c))
+            .collect();
+        Self::from_srgb(&stops)
+    }
+
+    // an ultra fractal .ugr, which can have a bunch of named gradients like
+    // `name { gradient: index=0 color=255 index=200 color=65280 }`. indices
+    // go up to 400, and colors are 0xbbggrr
+    pub fn parse_ugr(source: &str) -> Result<Vec<(String, Self)>, String> {
+        let mut res = Vec::new();
+        let mut rest = source;
+        while let Some(open) = rest.find('{') {
+            let name = rest[..open].trim().to_string();
+            let close = rest[open..]
+                .find('}')
+                .ok_or_else(|| format!("{}: missing }}", name))?;
+            let body = &rest[open + 1..open + close];
+            rest = &rest[open + close + 1..];
+
+            let mut stops = Vec::new();
+            let mut index = None;
+            for (key, value) in body.split_whitespace().filter_map(|w| w.split_once('=')) {
+                let num = || {
+                    value
+                        .parse::<i64>()
+                        .map_err(|_| format!("{}: {} isn't a number", name, value))
+                };
+                match key {
+                    "index" => index = Some(num()?),
+                    "color" => {
+                        let i = index
+                            .take()
+                            .ok_or_else(|| format!("{}: color without an index", name))?;
+                        let c = num()?;
+                        let pos = i.rem_euclid(UGR_LEN as i64) as f32 / UGR_LEN;
+                        let color = [c as u8, (c >> 8) as u8, (c >> 16) as u8];
+                        stops.push((pos, color));
+                    }
+                    _ => (),
+                }
+            }
+            let gradient = Self::from_srgb(&stops).map_err(|e| format!("{}: {}", name, e))?;
+            res.push((name, gradient));
+        }
+        if res.is_empty() {
+            return Err("no gradients".to_string());
+        }
+        Ok(res)
+    }
+
+    // the color at t, looping every 1
+    pub fn sample(&self, t: f32) -> [f32; 3] {
+        let t = t.rem_euclid(1.0);
+        let next = self.stops.partition_point(|s| s.pos <= t);
+        let (a, b) = match next {
+            0 => (self.stops[self.stops.len() - 1], self.stops[0]),
+            n if n == self.stops.len() => (self.stops[n - 1], self.stops[0]),
+            n => (self.stops[n - 1], self.stops[n]),
+        };
+        let span = (b.pos - a.pos).rem_euclid(1.0);
+        if span == 0.0 {
+            return a.color;
+        }
+        let f = (t - a.pos).rem_euclid(1.0) / span;
+        std::array::from_fn(|i| a.color[i] + (b.color[i] - a.color[i]) * f)
+    }
+
+    // len evenly spaced samples, for the palette texture
+    pub fn texels(&self, len: usize) -> Vec<[f32; 4]> {
+        (0..len)
+            .map(|i| {
+                let [r, g, b] = self.sample(i as f32 / len as f32);
+                [r, g, b, 1.0]
+            })
+            .collect()
+    }
+}
+
+fn linear(srgb: u8) -> f32 {
+    let c = srgb as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
@@ -0,0 +1,105 @@
+use super::{Gradient, Stop};
+
+fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6)
+}
+
+#[test]
+fn sample_wraps() {
+    let gradient = Gradient::new(vec![
+        Stop {
+            pos: 0.75,
+            color: [0.0, 0.0, 1.0],
+        },
+        Stop {
+            pos: 0.25,
+            color: [1.0, 0.0, 0.0],
+        },
+    ])
+    .unwrap();
+    assert!(close(gradient.sample(0.25), [1.0, 0.0, 0.0]));
+    assert!(close(gradient.sample(0.5), [0.5, 0.0, 0.5]));
+    // between the last stop and the first one again
+    assert!(close(gradient.sample(0.0), [0.5, 0.0, 0.5]));
+    assert!(close(gradient.sample(0.875), [0.25, 0.0, 0.75]));
+    assert!(close(gradient.sample(-0.75), gradient.sample(0.25)));
+    assert!(close(gradient.sample(1.5), gradient.sample(0.5)));
+}
+
+#[test]
+fn single_stop() {
+    let gradient = Gradient::from_srgb(&[(0.5, [255, 255, 255])]).unwrap();
+    assert!(close(gradient.sample(0.1), [1.0; 3]));
+    assert!(close(gradient.sample(0.9), [1.0; 3]));
+}
+
+#[test]
+fn bad_stops() {
+    assert!(Gradient::new(Vec::new()).is_err());
+    assert!(Gradient::from_srgb(&[(1.0, [0, 0, 0])]).is_err());
+}
+
+#[test]
+fn map() {
+    let gradient = Gradient::parse_map("255 0 0 red\n\n0 255 0\n0 0 255 ; blue\n0 0 0\n").unwrap();
+    assert!(close(gradient.sample(0.0), [1.0, 0.0, 0.0]));
+    assert!(close(gradient.sample(0.25), [0.0, 1.0, 0.0]));
+    assert!(close(gradient.sample(0.5), [0.0, 0.0, 1.0]));
+    assert!(close(gradient.sample(0.875), [0.5, 0.0, 0.0]));
+    // srgb to linear
+    let gray = Gradient::parse_map("128 128 128").unwrap().sample(0.0);
+    assert!((gray[0] - 0.21586).abs() < 1e-4, "{:?}", gray);
+}
+
+#[test]
+fn map_errors() {
+    assert_eq!(
+        Gradient::parse_map("0 0 0\n1 2\n").unwrap_err(),
+        "line 2: expected r g b from 0 to 255"
+    );
+    assert!(Gradient::parse_map("0 0 256").is_err());
+    assert!(Gradient::parse_map("").is_err());
+}
+
+#[test]
+fn ugr() {
+    let source = "
+first {
+gradient:
+  title=\"first\" smooth=yes
+  index=0 color=255
+  index=200 color=16711680
+opacity:
+  smooth=no index=0 opacity=255
+}
+
+second {
+gradient:
+  index=100 color=65280
+}
+";
+    let gradients = Gradient::parse_ugr(source).unwrap();
+    let names: Vec<_> = gradients.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["first", "second"]);
+    let first = &gradients[0].1;
+    assert!(close(first.sample(0.0), [1.0, 0.0, 0.0]));
+    assert!(close(first.sample(0.5), [0.0, 0.0, 1.0]));
+    assert!(close(first.sample(0.25), [0.5, 0.0, 0.5]));
+    assert!(close(gradients[1].1.sample(0.7), [0.0, 1.0, 0.0]));
+}
+
+#[test]
+fn ugr_errors() {
+    assert!(Gradient::parse_ugr("nothing here").is_err());
+    assert!(Gradient::parse_ugr("a { index=0 color=0").is_err());
+    assert!(Gradient::parse_ugr("a { gradient: }").is_err());
+    assert!(Gradient::parse_ugr("a { color=5 }").is_err());
+}
+
+#[test]
+fn texels() {
+    let texels = Gradient::hue().texels(1024);
+    assert_eq!(texels.len(), 1024);
+    assert!(close(texels[0][..3].try_into().unwrap(), [1.0, 0.0, 0.0]));
+    assert!(texels.iter().all(|t| t[3] == 1.0));
+}
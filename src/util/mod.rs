@@ -1,2 +1,8 @@
 mod fixed;
+mod floatexp;
+mod gradient;
+mod script;
 pub use fixed::*;
+pub use floatexp::*;
+pub use gradient::*;
+pub use script::*;
@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+
+use super::{
+    lex::Pos,
+    parse::{Cmp, Expr, Stmt},
+    Script, ScriptError,
+};
+
+// a real part or imaginary part; None is a known zero, so real values and
+// multiplying by them don't cost anything
+type Part = Option<String>;
+
+const ONE: &str = "from_f32(1.0)";
+
+#[derive(Debug, Clone)]
+struct Complex {
+    re: Part,
+    im: Part,
+}
+
+// emits every operation as its own let so repeated subexpressions only get
+// computed once; x2 and y2 are already in scope in the shader loop
+struct Gen {
+    code: String,
+    cache: HashMap<String, String>,
+    count: usize,
+}
+
+pub fn generate(stmts: &[Stmt]) -> Result<Script, ScriptError> {
+    let mut gen = Gen {
+        code: String::new(),
+        cache: HashMap::from([
+            ("mul(x, x)".to_string(), "x2".to_string()),
+            ("mul(y, y)".to_string(), "y2".to_string()),
+        ]),
+        count: 0,
+    };
+    let z = Complex {
+        re: Some("x".to_string()),
+        im: Some("y".to_string()),
+    };
+    let c = Complex {
+        re: Some("cx".to_string()),
+        im: Some("cy".to_string()),
+    };
+    let mut env = HashMap::from([("z".to_string(), z.clone()), ("c".to_string(), c)]);
+
+    // the bailout is checked at the start of every iteration, so it only
+    // sees the original z and c no matter where it's written
+    let mut bailouts = stmts.iter().filter_map(|stmt| match stmt {
+        Stmt::Bailout(lhs, cmp, rhs, pos) => Some((lhs, *cmp, rhs, *pos)),
+        Stmt::Assign(..) => None,
+    });
+    let escaped = match bailouts.next() {
+        Some((lhs, cmp, rhs, pos)) => {
+            let lhs = gen.expr(lhs, &env)?;
+            let rhs = gen.expr(rhs, &env)?;
+            if lhs.im.is_some() || rhs.im.is_some() {
+                return Err(ScriptError::new(
+                    pos,
+                    "can't compare complex numbers, use |z|, re() or im()".to_string(),
+                ));
+            }
+            gen.compare(lhs.re, cmp, rhs.re)
+        }
+        None => {
+            let norm = gen.norm(&z);
+            let thresh = gen.num(128.0 * 128.0);
+            gen.compare(norm, Cmp::Gt, thresh)
+        }
+    };
+    if let Some((.., pos)) = bailouts.next() {
+        return Err(ScriptError::new(
+            pos,
+            "there can only be one bailout".to_string(),
+        ));
+    }
+    gen.line(format!("let escaped = {};", escaped));
+    let bailout = std::mem::take(&mut gen.code);
+
+    for stmt in stmts {
+        let Stmt::Assign(name, pos, expr) = stmt else {
+            continue;
+        };
+        if name == "c" || name == "i" {
+            return Err(ScriptError::new(
+                *pos,
+                format!("{} can't be assigned", name),
+            ));
+        }
+        let val = gen.expr(expr, &env)?;
+        env.insert(name.clone(), val);
+    }
+    let next = &env["z"];
+    let next_x = next.re.clone().unwrap_or("zero()".to_string());
+    let next_y = next.im.clone().unwrap_or("zero()".to_string());
+    gen.line(format!("let next_x = {};", next_x));
+    gen.line(format!("let next_y = {};", next_y));
+    gen.line("x = next_x;".to_string());
+    gen.line("y = next_y;".to_string());
+
+    Ok(Script {
+        bailout,
+        body: gen.code,
+    })
+}
+
+impl Gen {
+    fn line(&mut self, line: String) {
+        self.code += "\n        ";
+        self.code += &line;
+    }
+
+    fn emit(&mut self, expr: String) -> String {
+        if let Some(name) = self.cache.get(&expr) {
+            return name.clone();
+        }
+        let name = format!("s{}", self.count);
+        self.count += 1;
+        self.line(format!("let {} = {};", name, expr));
+        self.cache.insert(expr, name.clone());
+        name
+    }
+
+    // copies a with its sign replaced, sign is wgsl for the new one
+    fn with_sign(&mut self, key: String, a: &str, sign: &str) -> String {
+        if let Some(name) = self.cache.get(&key) {
+            return name.clone();
+        }
+        let name = format!("s{}", self.count);
+        self.count += 1;
+        self.line(format!("var {} = {};", name, a));
+        self.line(format!("{}.sign = {};", name, sign));
+        self.cache.insert(key, name.clone());
+        name
+    }
+
+    fn num(&mut self, n: f32) -> Part {
+        (n != 0.0).then(|| self.emit(format!("from_f32({:?})", n)))
+    }
+
+    // operands of add and mul are sorted so a + b and b + a share a let
+    fn add(&mut self, a: Part, b: Part) -> Part {
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                let (a, b) = (a.clone().min(b.clone()), a.max(b));
+                Some(self.emit(format!("add({}, {})", a, b)))
+            }
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+
+    fn sub(&mut self, a: Part, b: Part) -> Part {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(self.emit(format!("sub({}, {})", a, b))),
+            (a, None) => a,
+            (None, b) => self.neg(b),
+        }
+    }
+
+    fn mul(&mut self, a: Part, b: Part) -> Part {
+        let (a, b) = (a?, b?);
+        let one = self.cache.get(ONE);
+        if one == Some(&a) {
+            return Some(b);
+        } else if one == Some(&b) {
+            return Some(a);
+        }
+        let (a, b) = (a.clone().min(b.clone()), a.max(b));
+        Some(self.emit(format!("mul({}, {})", a, b)))
+    }
+
+    fn neg(&mut self, a: Part) -> Part {
+        let a = a?;
+        Some(self.with_sign(
+            format!("neg({})", a),
+            &a,
+            &format!("u32({}.sign == POS)", a),
+        ))
+    }
+
+    fn abs(&mut self, a: Part) -> Part {
+        let a = a?;
+        Some(self.with_sign(format!("abs({})", a), &a, "POS"))
+    }
+
+    fn norm(&mut self, a: &Complex) -> Part {
+        let re = self.mul(a.re.clone(), a.re.clone());
+        let im = self.mul(a.im.clone(), a.im.clone());
+        self.add(re, im)
+    }
+
+    // ties count as true; fixed point can't really tell them apart anyway
+    fn compare(&mut self, a: Part, cmp: Cmp, b: Part) -> String {
+        let diff = match cmp {
+            Cmp::Gt | Cmp::Ge => self.sub(a, b),
+            Cmp::Lt | Cmp::Le => self.sub(b, a),
+        };
+        format!("{}.sign == POS", diff.unwrap_or("zero()".to_string()))
+    }
+
+    fn complex_mul(&mut self, a: &Complex, b: &Complex) -> Complex {
+        let ac = self.mul(a.re.clone(), b.re.clone());
+        let bd = self.mul(a.im.clone(), b.im.clone());
+        let ad = self.mul(a.re.clone(), b.im.clone());
+        let bc = self.mul(a.im.clone(), b.re.clone());
+        Complex {
+            re: self.sub(ac, bd),
+            im: self.add(ad, bc),
+        }
+    }
+
+    // (a + bi)^2 = a^2 - b^2 + 2abi, one less mul than complex_mul
+    fn complex_sqr(&mut self, a: &Complex) -> Complex {
+        let re2 = self.mul(a.re.clone(), a.re.clone());
+        let im2 = self.mul(a.im.clone(), a.im.clone());
+        let reim = self.mul(a.re.clone(), a.im.clone());
+        Complex {
+            re: self.sub(re2, im2),
+            im: self.add(reim.clone(), reim),
+        }
+    }
+
+    fn complex_pow(&mut self, a: &Complex, exp: u32) -> Complex {
+        let mut res: Option<Complex> = None;
+        let mut base = a.clone();
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = Some(match res {
+                    Some(res) => self.complex_mul(&res, &base),
+                    None => base.clone(),
+                });
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = self.complex_sqr(&base);
+            }
+        }
+        res.unwrap_or_else(|| Complex {
+            re: self.num(1.0),
+            im: None,
+        })
+    }
+
+    fn expr(
+        &mut self,
+        expr: &Expr,
+        env: &HashMap<String, Complex>,
+    ) -> Result<Complex, ScriptError> {
+        Ok(match expr {
+            Expr::Num(n) => Complex {
+                re: self.num(*n),
+                im: None,
+            },
+            Expr::I => Complex {
+                re: None,
+                im: self.num(1.0),
+            },
+            Expr::Var(name, pos) => env
+                .get(name)
+                .cloned()
+                .ok_or_else(|| unknown(*pos, "variable", name))?,
+            Expr::Neg(a) => {
+                let a = self.expr(a, env)?;
+                Complex {
+                    re: self.neg(a.re),
+                    im: self.neg(a.im),
+                }
+            }
+            Expr::Add(a, b) => {
+                let (a, b) = (self.expr(a, env)?, self.expr(b, env)?);
+                Complex {
+                    re: self.add(a.re, b.re),
+                    im: self.add(a.im, b.im),
+                }
+            }
+            Expr::Sub(a, b) => {
+                let (a, b) = (self.expr(a, env)?, self.expr(b, env)?);
+                Complex {
+                    re: self.sub(a.re, b.re),
+                    im: self.sub(a.im, b.im),
+                }
+            }
+            Expr::Mul(a, b) => {
+                let (a, b) = (self.expr(a, env)?, self.expr(b, env)?);
+                self.complex_mul(&a, &b)
+            }
+            // even powers of the modulus don't need the square root
+            Expr::Pow(a, exp) if exp % 2 == 0 && matches!(**a, Expr::Modulus(_)) => {
+                let Expr::Modulus(a) = &**a else {
+                    unreachable!()
+                };
+                let a = self.expr(a, env)?;
+                let norm = Complex {
+                    re: self.norm(&a),
+                    im: None,
+                };
+                self.complex_pow(&norm, exp / 2)
+            }
+            Expr::Pow(a, exp) => {
+                let a = self.expr(a, env)?;
+                self.complex_pow(&a, *exp)
+            }
+            Expr::Modulus(a) => {
+                let a = self.expr(a, env)?;
+                let re = if a.im.is_none() {
+                    self.abs(a.re)
+                } else {
+                    let norm = self.norm(&a);
+                    norm.map(|norm| self.emit(format!("sqrt({})", norm)))
+                };
+                Complex { re, im: None }
+            }
+            Expr::Call(name, a, pos) => {
+                let a = self.expr(a, env)?;
+                match name.as_str() {
+                    "abs" => Complex {
+                        re: self.abs(a.re),
+                        im: self.abs(a.im),
+                    },
+                    "conj" => Complex {
+                        re: a.re,
+                        im: self.neg(a.im),
+                    },
+                    "re" => Complex { re: a.re, im: None },
+                    "im" => Complex { re: a.im, im: None },
+                    _ => return Err(unknown(*pos, "function", name)),
+                }
+            }
+        })
+    }
+}
+
+fn unknown(pos: Pos, kind: &str, name: &str) -> ScriptError {
+    ScriptError::new(pos, format!("unknown {} '{}'", kind, name))
+}
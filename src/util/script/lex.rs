@@ -0,0 +1,127 @@
+use super::ScriptError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tok {
+    Num(f32),
+    Int(u32),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    Bar,
+    LParen,
+    RParen,
+    Assign,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    // ; or a newline
+    End,
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub tok: Tok,
+    pub pos: Pos,
+}
+
+pub fn lex(source: &str) -> Result<Vec<Token>, ScriptError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut pos = Pos { line: 1, col: 1 };
+    while let Some(&ch) = chars.peek() {
+        let start = pos;
+        let mut push = |tok| tokens.push(Token { tok, pos: start });
+        if ch.is_ascii_digit() || ch == '.' {
+            let mut text = String::new();
+            while let Some(&c) = chars.peek() {
+                let exp_sign = (c == '-' || c == '+') && text.ends_with(['e', 'E']);
+                if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exp_sign) {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+                pos.col += 1;
+            }
+            let tok =
+                match text.parse::<u32>() {
+                    Ok(int) => Tok::Int(int),
+                    Err(_) => Tok::Num(text.parse().map_err(|_| {
+                        ScriptError::new(start, format!("invalid number '{}'", text))
+                    })?),
+                };
+            push(tok);
+            continue;
+        }
+        if ch.is_alphabetic() || ch == '_' {
+            let mut text = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+                pos.col += 1;
+            }
+            push(Tok::Ident(text));
+            continue;
+        }
+
+        chars.next();
+        pos.col += 1;
+        let mut followed_by = |next| {
+            let res = chars.peek() == Some(&next);
+            if res {
+                chars.next();
+                pos.col += 1;
+            }
+            res
+        };
+        let tok = match ch {
+            '\n' => {
+                pos.line += 1;
+                pos.col = 1;
+                Tok::End
+            }
+            ';' => Tok::End,
+            '#' => {
+                // comment until the end of the line, which still ends the statement
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '+' => Tok::Plus,
+            '-' => Tok::Minus,
+            '*' => Tok::Star,
+            '/' => Tok::Slash,
+            '^' => Tok::Caret,
+            '|' => Tok::Bar,
+            '(' => Tok::LParen,
+            ')' => Tok::RParen,
+            '=' => Tok::Assign,
+            '>' if followed_by('=') => Tok::Ge,
+            '>' => Tok::Gt,
+            '<' if followed_by('=') => Tok::Le,
+            '<' => Tok::Lt,
+            c => {
+                return Err(ScriptError::new(
+                    start,
+                    format!("unexpected character '{}'", c),
+                ))
+            }
+        };
+        push(tok);
+    }
+    tokens.push(Token { tok: Tok::Eof, pos });
+    Ok(tokens)
+}
@@ -0,0 +1,61 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use lex::Pos;
+
+mod codegen;
+mod lex;
+mod parse;
+#[cfg(test)]
+mod test;
+
+// a formula written as a little language over complex numbers, e.g.
+// `z = z*z + c; bailout |z|^2 > 4`, compiled to the fixed point functions in
+// compute/fixed.wgsl. z is the iterated value and c the pixel (or the julia
+// constant); there's +, -, *, integer powers, |z|, abs (of both parts), conj,
+// re and im. the bailout defaults to |z|^2 > 128^2, the same as the builtin
+// formulas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    // statements ending in `let escaped = ...;`
+    pub bailout: String,
+    // statements setting x and y to the next z
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub col: usize,
+    pub msg: String,
+}
+
+impl ScriptError {
+    fn new(pos: Pos, msg: String) -> Self {
+        Self {
+            line: pos.line,
+            col: pos.col,
+            msg,
+        }
+    }
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.msg)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+impl FromStr for Script {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = lex::lex(s)?;
+        let stmts = parse::parse(&tokens)?;
+        codegen::generate(&stmts)
+    }
+}
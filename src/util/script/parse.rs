@@ -0,0 +1,242 @@
+use super::{
+    lex::{Pos, Tok, Token},
+    ScriptError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Num(f32),
+    // the imaginary unit
+    I,
+    Var(String, Pos),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, u32),
+    // |e|
+    Modulus(Box<Expr>),
+    Call(String, Box<Expr>, Pos),
+}
+
+#[derive(Debug, Clone)]
+pub enum Stmt {
+    Assign(String, Pos, Expr),
+    Bailout(Expr, Cmp, Expr, Pos),
+}
+
+// program = stmt? (End stmt?)* Eof
+// stmt = ident '=' expr | 'bailout' expr cmp expr
+// expr = term (('+' | '-') term)*
+// term = unary ('*' unary)*
+// unary = '-' unary | power
+// power = atom ('^' int)*
+// atom = num | ident | ident '(' expr ')' | '(' expr ')' | '|' expr '|'
+pub fn parse(tokens: &[Token]) -> Result<Vec<Stmt>, ScriptError> {
+    let mut parser = Parser { tokens, i: 0 };
+    let mut stmts = Vec::new();
+    loop {
+        match parser.peek() {
+            Tok::Eof => break,
+            Tok::End => {
+                parser.next();
+            }
+            _ => {
+                stmts.push(parser.stmt()?);
+                if !matches!(parser.peek(), Tok::End | Tok::Eof) {
+                    return Err(parser.unexpected("end of statement"));
+                }
+            }
+        }
+    }
+    Ok(stmts)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    i: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Tok {
+        &self.tokens[self.i].tok
+    }
+
+    fn pos(&self) -> Pos {
+        self.tokens[self.i].pos
+    }
+
+    fn next(&mut self) -> &Token {
+        let token = &self.tokens[self.i];
+        if token.tok != Tok::Eof {
+            self.i += 1;
+        }
+        token
+    }
+
+    fn unexpected(&self, expected: &str) -> ScriptError {
+        let found = match self.peek() {
+            Tok::Num(n) => format!("'{}'", n),
+            Tok::Int(n) => format!("'{}'", n),
+            Tok::Ident(s) => format!("'{}'", s),
+            Tok::End => "end of statement".to_string(),
+            Tok::Eof => "end of file".to_string(),
+            tok => format!("'{}'", symbol(tok)),
+        };
+        ScriptError::new(
+            self.pos(),
+            format!("expected {}, found {}", expected, found),
+        )
+    }
+
+    fn expect(&mut self, tok: Tok) -> Result<(), ScriptError> {
+        if *self.peek() != tok {
+            return Err(self.unexpected(&format!("'{}'", symbol(&tok))));
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, ScriptError> {
+        let pos = self.pos();
+        let Tok::Ident(name) = self.peek().clone() else {
+            return Err(self.unexpected("assignment or bailout"));
+        };
+        self.next();
+        if name == "bailout" {
+            let lhs = self.expr()?;
+            let cmp_pos = self.pos();
+            let cmp = match self.peek() {
+                Tok::Gt => Cmp::Gt,
+                Tok::Ge => Cmp::Ge,
+                Tok::Lt => Cmp::Lt,
+                Tok::Le => Cmp::Le,
+                _ => return Err(self.unexpected("comparison")),
+            };
+            self.next();
+            let rhs = self.expr()?;
+            return Ok(Stmt::Bailout(lhs, cmp, rhs, cmp_pos));
+        }
+        self.expect(Tok::Assign)?;
+        Ok(Stmt::Assign(name, pos, self.expr()?))
+    }
+
+    fn expr(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.term()?;
+        loop {
+            lhs = match self.peek() {
+                Tok::Plus => {
+                    self.next();
+                    Expr::Add(Box::new(lhs), Box::new(self.term()?))
+                }
+                Tok::Minus => {
+                    self.next();
+                    Expr::Sub(Box::new(lhs), Box::new(self.term()?))
+                }
+                _ => return Ok(lhs),
+            };
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.unary()?;
+        loop {
+            match self.peek() {
+                Tok::Star => {
+                    self.next();
+                    lhs = Expr::Mul(Box::new(lhs), Box::new(self.unary()?));
+                }
+                Tok::Slash => {
+                    return Err(ScriptError::new(
+                        self.pos(),
+                        "division isn't supported in fixed point".to_string(),
+                    ));
+                }
+                _ => return Ok(lhs),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ScriptError> {
+        if *self.peek() == Tok::Minus {
+            self.next();
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, ScriptError> {
+        let mut base = self.atom()?;
+        while *self.peek() == Tok::Caret {
+            self.next();
+            let Tok::Int(exp) = *self.peek() else {
+                return Err(self.unexpected("a whole number exponent"));
+            };
+            self.next();
+            base = Expr::Pow(Box::new(base), exp);
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, ScriptError> {
+        let pos = self.pos();
+        let expr = match self.peek().clone() {
+            Tok::Num(n) => Expr::Num(n),
+            Tok::Int(n) => Expr::Num(n as f32),
+            Tok::Ident(name) if name == "i" => Expr::I,
+            Tok::Ident(name) => {
+                self.next();
+                if *self.peek() != Tok::LParen {
+                    return Ok(Expr::Var(name, pos));
+                }
+                self.next();
+                let arg = self.expr()?;
+                self.expect(Tok::RParen)?;
+                return Ok(Expr::Call(name, Box::new(arg), pos));
+            }
+            Tok::LParen => {
+                self.next();
+                let expr = self.expr()?;
+                self.expect(Tok::RParen)?;
+                return Ok(expr);
+            }
+            Tok::Bar => {
+                self.next();
+                let expr = self.expr()?;
+                self.expect(Tok::Bar)?;
+                return Ok(Expr::Modulus(Box::new(expr)));
+            }
+            _ => return Err(self.unexpected("expression")),
+        };
+        self.next();
+        Ok(expr)
+    }
+}
+
+fn symbol(tok: &Tok) -> &'static str {
+    match tok {
+        Tok::Plus => "+",
+        Tok::Minus => "-",
+        Tok::Star => "*",
+        Tok::Slash => "/",
+        Tok::Caret => "^",
+        Tok::Bar => "|",
+        Tok::LParen => "(",
+        Tok::RParen => ")",
+        Tok::Assign => "=",
+        Tok::Gt => ">",
+        Tok::Ge => ">=",
+        Tok::Lt => "<",
+        Tok::Le => "<=",
+        Tok::End => ";",
+        Tok::Num(_) | Tok::Int(_) | Tok::Ident(_) | Tok::Eof => "",
+    }
+}
@@ -0,0 +1,103 @@
+use super::{Script, ScriptError};
+
+fn err(source: &str) -> (usize, usize, String) {
+    let ScriptError { line, col, msg } = source.parse::<Script>().unwrap_err();
+    (line, col, msg)
+}
+
+#[test]
+fn mandelbrot() {
+    let script: Script = "z = z*z + c; bailout |z|^2 > 4".parse().unwrap();
+    assert_eq!(
+        script.bailout,
+        "
+        let s0 = add(x2, y2);
+        let s1 = from_f32(4.0);
+        let s2 = sub(s0, s1);
+        let escaped = s2.sign == POS;"
+    );
+    assert_eq!(
+        script.body,
+        "
+        let s3 = mul(x, y);
+        let s4 = sub(x2, y2);
+        let s5 = add(s3, s3);
+        let s6 = add(cx, s4);
+        let s7 = add(cy, s5);
+        let next_x = s6;
+        let next_y = s7;
+        x = next_x;
+        y = next_y;"
+    );
+}
+
+#[test]
+fn default_bailout() {
+    let explicit: Script = "z = z^2 + c\nbailout |z|^2 > 16384".parse().unwrap();
+    let default: Script = "z = z^2 + c".parse().unwrap();
+    assert_eq!(explicit, default);
+}
+
+#[test]
+fn reuse() {
+    // z^2 squares with x2 and y2, and the two abs calls share one copy
+    let script: Script = "z = z^2 + abs(c) + abs(c)".parse().unwrap();
+    assert!(!script.body.contains("mul(x, x)"));
+    assert_eq!(script.body.matches(".sign = POS;").count(), 2);
+    assert!(script.body.contains("sub(x2, y2)"));
+}
+
+#[test]
+fn real_parts() {
+    // multiplying by a real only touches the parts that aren't zero
+    let script: Script = "z = 2*z + re(c)".parse().unwrap();
+    assert_eq!(script.body.matches("mul(").count(), 2);
+    assert!(script.body.contains("let next_y = s5;"));
+    let script: Script = "z = i*z".parse().unwrap();
+    assert!(!script.body.contains("mul("));
+    let script: Script = "z = i".parse().unwrap();
+    assert!(script.body.contains("let next_x = zero();"));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        err("z = z*z +"),
+        (1, 10, "expected expression, found end of file".into())
+    );
+    assert_eq!(
+        err("z = z^2 + c\nz = z $ c"),
+        (2, 7, "unexpected character '$'".into())
+    );
+    assert_eq!(
+        err("z = z^2 + c\n  bailout z > 4"),
+        (
+            2,
+            13,
+            "can't compare complex numbers, use |z|, re() or im()".into()
+        )
+    );
+    assert_eq!(err("z = w + c"), (1, 5, "unknown variable 'w'".into()));
+    assert_eq!(err("z = sin(z)"), (1, 5, "unknown function 'sin'".into()));
+    assert_eq!(
+        err("z = z^1.5"),
+        (1, 7, "expected a whole number exponent, found '1.5'".into())
+    );
+    assert_eq!(err("c = z"), (1, 1, "c can't be assigned".into()));
+    assert_eq!(
+        err("z = z / c"),
+        (1, 7, "division isn't supported in fixed point".into())
+    );
+    assert_eq!(
+        err("# comment\nz = (z + c"),
+        (2, 11, "expected ')', found end of file".into())
+    );
+    assert_eq!(
+        err("z = z c"),
+        (1, 7, "expected end of statement, found 'c'".into())
+    );
+    assert_eq!(
+        err("bailout |z| > 2; bailout |z| > 3"),
+        (1, 30, "there can only be one bailout".into())
+    );
+}